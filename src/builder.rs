@@ -0,0 +1,1143 @@
+//! Incremental array construction.
+//!
+//! [`PartiallyInitArray`] is the building block behind macros of this crate.
+//! It can be used directly when control flow is too complex for the macros.
+//!
+//! ```
+//! # use array_fu::builder::PartiallyInitArray;
+//! let mut builder = PartiallyInitArray::<u32, 3>::uninit();
+//!
+//! for word in "one two three four".split(' ') {
+//!     if builder.push(word.len() as u32).is_err() {
+//!         break;
+//!     }
+//! }
+//!
+//! assert!(builder.is_full());
+//! assert_eq!(builder.try_init(), Some([3, 3, 5]));
+//! ```
+
+use core::{
+    fmt,
+    iter::FusedIterator,
+    mem::{self, MaybeUninit},
+    ptr,
+};
+
+#[cfg(feature = "alloc")]
+use {
+    alloc::{boxed::Box, vec::Vec},
+    core::{alloc::Layout, mem::ManuallyDrop, ptr::NonNull, slice},
+};
+
+/// Array of `N` elements of type `T` initialized front-to-back.
+///
+/// Written elements are dropped with the builder
+/// unless it is turned into an array.
+pub struct PartiallyInitArray<T, const N: usize> {
+    array: [MaybeUninit<T>; N],
+    init: usize,
+}
+
+impl<T, const N: usize> PartiallyInitArray<T, N> {
+    /// Returns empty builder.
+    #[inline]
+    pub fn uninit() -> Self {
+        PartiallyInitArray {
+            array: crate::uninit_array(),
+            init: 0,
+        }
+    }
+
+    /// Writes next element.
+    ///
+    /// # Safety
+    ///
+    /// Must be called at most `N` times.
+    /// Or equivalently, until `is_full` returns true.
+    #[inline]
+    pub unsafe fn write(&mut self, value: T) {
+        debug_assert!(self.init < N);
+        self.array[self.init].write(value);
+        self.init += 1;
+    }
+
+    /// Writes next element if array is not full yet.
+    /// Otherwise returns value back.
+    #[inline]
+    pub fn push(&mut self, value: T) -> Result<(), T> {
+        if self.init < N {
+            unsafe {
+                // SAFETY: Not fully initialized.
+                self.write(value);
+            }
+            Ok(())
+        } else {
+            Err(value)
+        }
+    }
+
+    /// Removes last written element and returns it.
+    /// Returns `None` if no elements were written.
+    #[inline]
+    pub fn pop(&mut self) -> Option<T> {
+        if self.init == 0 {
+            return None;
+        }
+
+        self.init -= 1;
+        let value = unsafe {
+            // SAFETY: Element at `init` was initialized and is no longer tracked.
+            self.array[self.init].assume_init_read()
+        };
+        Some(value)
+    }
+
+    /// Same as [`push`](Self::push).
+    #[deprecated(note = "use `push` instead")]
+    #[inline]
+    pub fn try_write(&mut self, value: T) -> Result<(), T> {
+        self.push(value)
+    }
+
+    /// Copies as many elements from `src` as fit into the array.
+    /// Returns number of elements written.
+    #[inline]
+    pub fn extend_from_slice(&mut self, src: &[T]) -> usize
+    where
+        T: Copy,
+    {
+        let count = src.len().min(N - self.init);
+        unsafe {
+            // SAFETY: `count` elements fit into uninitialized tail of the array.
+            ptr::copy_nonoverlapping(
+                src.as_ptr(),
+                self.array[self.init..].as_mut_ptr() as *mut T,
+                count,
+            );
+        }
+        self.init += count;
+        count
+    }
+
+    /// Writes values from the iterator until array is full or iterator ends.
+    /// Returns number of elements written.
+    ///
+    /// Iterator is not advanced once array is full.
+    #[inline]
+    pub fn extend_from_iter<I>(&mut self, iter: I) -> usize
+    where
+        I: IntoIterator<Item = T>,
+    {
+        let start = self.init;
+        let mut iter = iter.into_iter();
+        while self.init < N {
+            match iter.next() {
+                None => break,
+                Some(value) => unsafe {
+                    // SAFETY: Not fully initialized.
+                    self.write(value);
+                },
+            }
+        }
+        self.init - start
+    }
+
+    /// Returns array filled with values from the iterator.
+    /// Returns `None` if iterator ends before array is filled.
+    ///
+    /// Iterator is not advanced once array is full.
+    #[inline]
+    pub fn from_iter_checked<I>(iter: I) -> Option<[T; N]>
+    where
+        I: IntoIterator<Item = T>,
+    {
+        let mut array = Self::uninit();
+        array.extend_from_iter(iter);
+        array.try_init()
+    }
+
+    /// Fills remaining elements with values returned by `f`.
+    #[inline]
+    pub fn fill_with(&mut self, mut f: impl FnMut() -> T) {
+        while self.init < N {
+            unsafe {
+                // SAFETY: Not fully initialized.
+                self.write(f());
+            }
+        }
+    }
+
+    /// Fills remaining elements with clones of `value`.
+    /// Original value is moved into the last element.
+    /// It is dropped if array is already full.
+    #[inline]
+    pub fn fill_cloned(&mut self, value: T)
+    where
+        T: Clone,
+    {
+        if self.init == N {
+            return;
+        }
+
+        while self.init < N - 1 {
+            unsafe {
+                // SAFETY: Not fully initialized.
+                self.write(value.clone());
+            }
+        }
+
+        unsafe {
+            // SAFETY: Exactly one element left.
+            self.write(value);
+        }
+    }
+
+    /// Fills remaining elements with values returned by `f`.
+    /// Stops on first error and returns it.
+    #[inline]
+    pub fn fill_with_result<E>(&mut self, mut f: impl FnMut() -> Result<T, E>) -> Result<(), E> {
+        while self.init < N {
+            let value = f()?;
+            unsafe {
+                // SAFETY: Not fully initialized.
+                self.write(value);
+            }
+        }
+        Ok(())
+    }
+
+    /// Returns true if all `N` elements are written.
+    #[inline]
+    pub fn is_full(&self) -> bool {
+        self.init == N
+    }
+
+    /// Same as [`is_full`](Self::is_full).
+    #[deprecated(note = "use `is_full` instead")]
+    #[inline]
+    pub fn is_init(&self) -> bool {
+        self.is_full()
+    }
+
+    /// Returns number of already written elements.
+    #[inline]
+    pub fn len(&self) -> usize {
+        self.init
+    }
+
+    /// Returns number of elements left to write.
+    #[inline]
+    pub fn remaining(&self) -> usize {
+        N - self.init
+    }
+
+    /// Returns true if no elements were written yet.
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.init == 0
+    }
+
+    /// Returns slice of already written elements.
+    #[inline]
+    pub fn as_initialized_slice(&self) -> &[T] {
+        let slice = &self.array[..self.init];
+        unsafe {
+            // SAFETY: First `init` elements are initialized.
+            &*(slice as *const [MaybeUninit<T>] as *const [T])
+        }
+    }
+
+    /// Returns mutable slice of already written elements.
+    #[inline]
+    pub fn as_initialized_slice_mut(&mut self) -> &mut [T] {
+        let slice = &mut self.array[..self.init];
+        unsafe {
+            // SAFETY: First `init` elements are initialized.
+            &mut *(slice as *mut [MaybeUninit<T>] as *mut [T])
+        }
+    }
+
+    /// Same as [`as_initialized_slice`](Self::as_initialized_slice).
+    #[deprecated(note = "use `as_initialized_slice` instead")]
+    #[inline]
+    pub fn as_init_slice(&self) -> &[T] {
+        self.as_initialized_slice()
+    }
+
+    /// Same as [`as_initialized_slice_mut`](Self::as_initialized_slice_mut).
+    #[deprecated(note = "use `as_initialized_slice_mut` instead")]
+    #[inline]
+    pub fn as_init_mut_slice(&mut self) -> &mut [T] {
+        self.as_initialized_slice_mut()
+    }
+
+    /// Returns fully initialized array.
+    ///
+    /// # Safety
+    ///
+    /// Must be called after `write` was called exactly `N` times.
+    /// Or equivalently, when `is_full` returns true.
+    #[inline]
+    pub unsafe fn assume_init(self) -> [T; N] {
+        debug_assert_eq!(self.init, N);
+        let array = {
+            // SAFETY: Fully initialized.
+            mem::transmute_copy::<[MaybeUninit<T>; N], [T; N]>(&self.array)
+        };
+        mem::forget(self);
+        array
+    }
+
+    /// Returns fully initialized array.
+    /// Otherwise drops written elements and returns `None`.
+    #[inline]
+    pub fn try_init(self) -> Option<[T; N]> {
+        self.try_into_array().ok()
+    }
+
+    /// Drops all written elements, making array empty again.
+    #[inline]
+    pub fn reset(&mut self) {
+        let init = mem::replace(&mut self.init, 0);
+        let slice = &mut self.array[..init];
+        unsafe { ptr::drop_in_place(slice as *mut [MaybeUninit<T>] as *mut [T]) }
+    }
+
+    /// Returns fully initialized array,
+    /// filling remaining elements with values returned by `f` first.
+    #[inline]
+    pub fn try_init_or_fill(mut self, f: impl FnMut() -> T) -> [T; N] {
+        self.fill_with(f);
+        unsafe {
+            // SAFETY: `fill_with` initializes all remaining elements.
+            self.assume_init()
+        }
+    }
+
+    /// Returns fully initialized array.
+    /// Otherwise returns partially initialized array back.
+    #[inline]
+    pub fn try_into_array(self) -> Result<[T; N], Self> {
+        if self.init == N {
+            let array = unsafe {
+                // SAFETY: Fully initialized.
+                mem::transmute_copy::<[MaybeUninit<T>; N], [T; N]>(&self.array)
+            };
+            mem::forget(self);
+            Ok(array)
+        } else {
+            Err(self)
+        }
+    }
+}
+
+impl<T, const N: usize> Drop for PartiallyInitArray<T, N> {
+    fn drop(&mut self) {
+        let slice = &mut self.array[..self.init];
+        unsafe { ptr::drop_in_place(slice as *mut [MaybeUninit<T>] as *mut [T]) }
+    }
+}
+
+impl<T, const N: usize> Clone for PartiallyInitArray<T, N>
+where
+    T: Clone,
+{
+    fn clone(&self) -> Self {
+        let mut array = PartiallyInitArray::uninit();
+        for value in self.as_initialized_slice() {
+            unsafe {
+                // SAFETY: Source array has no more than `N` elements.
+                // If `clone` panics `array` drops elements written so far.
+                array.write(value.clone());
+            }
+        }
+        array
+    }
+}
+
+/// Formats written elements and number of elements left to write.
+/// Uninitialized elements are never read.
+///
+/// ```
+/// # use array_fu::builder::PartiallyInitArray;
+/// let mut builder = PartiallyInitArray::<u32, 4>::uninit();
+/// builder.extend([1, 2]);
+///
+/// assert_eq!(
+///     format!("{:?}", builder),
+///     "PartiallyInitArray { initialized: [1, 2], remaining: 2 }"
+/// );
+/// ```
+impl<T, const N: usize> fmt::Debug for PartiallyInitArray<T, N>
+where
+    T: fmt::Debug,
+{
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("PartiallyInitArray")
+            .field("initialized", &self.as_initialized_slice())
+            .field("remaining", &self.remaining())
+            .finish()
+    }
+}
+
+/// Writes values until array is full.
+/// Values beyond array capacity are not consumed from the iterator.
+impl<T, const N: usize> Extend<T> for PartiallyInitArray<T, N> {
+    #[inline]
+    fn extend<I>(&mut self, iter: I)
+    where
+        I: IntoIterator<Item = T>,
+    {
+        self.extend_from_iter(iter);
+    }
+}
+
+/// Consumes array yielding written elements by value.
+/// Elements not yielded are dropped with the iterator.
+///
+/// ```
+/// # use array_fu::builder::PartiallyInitArray;
+/// let mut builder = PartiallyInitArray::<String, 4>::uninit();
+/// builder.extend(["a".to_owned(), "b".to_owned()]);
+///
+/// let joined: String = builder.into_iter().collect();
+/// assert_eq!(joined, "ab");
+/// ```
+impl<T, const N: usize> IntoIterator for PartiallyInitArray<T, N> {
+    type Item = T;
+    type IntoIter = PartiallyInitArrayIntoIter<T, N>;
+
+    #[inline]
+    fn into_iter(self) -> PartiallyInitArrayIntoIter<T, N> {
+        let this = mem::ManuallyDrop::new(self);
+        PartiallyInitArrayIntoIter {
+            // SAFETY: `this` is never dropped, so array is moved out exactly once.
+            array: unsafe { ptr::read(&this.array) },
+            next: 0,
+            init: this.init,
+        }
+    }
+}
+
+/// Iterator over initialized elements of the [`PartiallyInitArray`].
+pub struct PartiallyInitArrayIntoIter<T, const N: usize> {
+    array: [MaybeUninit<T>; N],
+    next: usize,
+    init: usize,
+}
+
+impl<T, const N: usize> Iterator for PartiallyInitArrayIntoIter<T, N> {
+    type Item = T;
+
+    #[inline]
+    fn next(&mut self) -> Option<T> {
+        if self.next < self.init {
+            let value = unsafe {
+                // SAFETY: Elements in `next..init` are initialized and not yet read.
+                self.array[self.next].assume_init_read()
+            };
+            self.next += 1;
+            Some(value)
+        } else {
+            None
+        }
+    }
+
+    #[inline]
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        let len = self.init - self.next;
+        (len, Some(len))
+    }
+}
+
+impl<T, const N: usize> ExactSizeIterator for PartiallyInitArrayIntoIter<T, N> {}
+
+impl<T, const N: usize> FusedIterator for PartiallyInitArrayIntoIter<T, N> {}
+
+impl<T, const N: usize> Drop for PartiallyInitArrayIntoIter<T, N> {
+    fn drop(&mut self) {
+        let slice = &mut self.array[self.next..self.init];
+        unsafe { ptr::drop_in_place(slice as *mut [MaybeUninit<T>] as *mut [T]) }
+    }
+}
+
+/// Array of `N` elements of type `T` initialized back-to-front.
+///
+/// Written elements form a suffix of the array
+/// and are dropped with the builder unless it is turned into an array.
+///
+/// ```
+/// # use array_fu::builder::RevPartiallyInitArray;
+/// let mut builder = RevPartiallyInitArray::<u32, 3>::uninit();
+///
+/// unsafe {
+///     builder.write_back(3);
+///     builder.write_back(2);
+/// }
+/// assert_eq!(builder.as_initialized_slice(), [2, 3]);
+///
+/// unsafe {
+///     builder.write_back(1);
+/// }
+/// assert_eq!(builder.try_init(), Some([1, 2, 3]));
+/// ```
+pub struct RevPartiallyInitArray<T, const N: usize> {
+    array: [MaybeUninit<T>; N],
+    init: usize,
+}
+
+impl<T, const N: usize> RevPartiallyInitArray<T, N> {
+    /// Returns empty builder.
+    #[inline]
+    pub fn uninit() -> Self {
+        RevPartiallyInitArray {
+            array: crate::uninit_array(),
+            init: 0,
+        }
+    }
+
+    /// Writes element right before already written ones.
+    ///
+    /// # Safety
+    ///
+    /// Must be called at most `N` times.
+    /// Or equivalently, until `is_full` returns true.
+    #[inline]
+    pub unsafe fn write_back(&mut self, value: T) {
+        debug_assert!(self.init < N);
+        self.init += 1;
+        self.array[N - self.init].write(value);
+    }
+
+    /// Returns true if all `N` elements are written.
+    #[inline]
+    pub fn is_full(&self) -> bool {
+        self.init == N
+    }
+
+    /// Returns number of already written elements.
+    #[inline]
+    pub fn len(&self) -> usize {
+        self.init
+    }
+
+    /// Returns number of elements left to write.
+    #[inline]
+    pub fn remaining(&self) -> usize {
+        N - self.init
+    }
+
+    /// Returns true if no elements were written yet.
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.init == 0
+    }
+
+    /// Returns slice of already written elements in array order.
+    #[inline]
+    pub fn as_initialized_slice(&self) -> &[T] {
+        let slice = &self.array[N - self.init..];
+        unsafe {
+            // SAFETY: Last `init` elements are initialized.
+            &*(slice as *const [MaybeUninit<T>] as *const [T])
+        }
+    }
+
+    /// Returns mutable slice of already written elements in array order.
+    #[inline]
+    pub fn as_initialized_slice_mut(&mut self) -> &mut [T] {
+        let slice = &mut self.array[N - self.init..];
+        unsafe {
+            // SAFETY: Last `init` elements are initialized.
+            &mut *(slice as *mut [MaybeUninit<T>] as *mut [T])
+        }
+    }
+
+    /// Returns fully initialized array.
+    ///
+    /// # Safety
+    ///
+    /// Must be called after `write_back` was called exactly `N` times.
+    /// Or equivalently, when `is_full` returns true.
+    #[inline]
+    pub unsafe fn assume_init(self) -> [T; N] {
+        debug_assert_eq!(self.init, N);
+        let array = {
+            // SAFETY: Fully initialized.
+            mem::transmute_copy::<[MaybeUninit<T>; N], [T; N]>(&self.array)
+        };
+        mem::forget(self);
+        array
+    }
+
+    /// Returns fully initialized array.
+    /// Otherwise drops written elements and returns `None`.
+    #[inline]
+    pub fn try_init(self) -> Option<[T; N]> {
+        if self.init == N {
+            Some(unsafe {
+                // SAFETY: Fully initialized.
+                self.assume_init()
+            })
+        } else {
+            None
+        }
+    }
+}
+
+impl<T, const N: usize> Drop for RevPartiallyInitArray<T, N> {
+    fn drop(&mut self) {
+        let slice = &mut self.array[N - self.init..];
+        unsafe { ptr::drop_in_place(slice as *mut [MaybeUninit<T>] as *mut [T]) }
+    }
+}
+
+impl<T, const N: usize> fmt::Debug for RevPartiallyInitArray<T, N>
+where
+    T: fmt::Debug,
+{
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("RevPartiallyInitArray")
+            .field("initialized", &self.as_initialized_slice())
+            .field("remaining", &self.remaining())
+            .finish()
+    }
+}
+
+/// Heap-allocated counterpart of [`PartiallyInitArray`].
+///
+/// Storage is allocated up front and elements are written in place,
+/// so arrays too large for the stack can be built.
+#[cfg(feature = "alloc")]
+pub struct BoxedPartiallyInitArray<T, const N: usize> {
+    array: Box<[MaybeUninit<T>; N]>,
+    init: usize,
+}
+
+#[cfg(feature = "alloc")]
+impl<T, const N: usize> BoxedPartiallyInitArray<T, N> {
+    /// Returns empty builder.
+    #[inline]
+    pub fn uninit() -> Self {
+        BoxedPartiallyInitArray {
+            array: boxed_uninit_array(),
+            init: 0,
+        }
+    }
+
+    /// Writes next element.
+    ///
+    /// # Safety
+    ///
+    /// Must be called at most `N` times.
+    /// Or equivalently, until `is_full` returns true.
+    #[inline]
+    pub unsafe fn write(&mut self, value: T) {
+        debug_assert!(self.init < N);
+        self.array[self.init].write(value);
+        self.init += 1;
+    }
+
+    /// Returns true if all elements are written.
+    #[inline]
+    pub fn is_full(&self) -> bool {
+        self.init == N
+    }
+
+    /// Same as [`is_full`](Self::is_full).
+    #[deprecated(note = "use `is_full` instead")]
+    #[inline]
+    pub fn is_init(&self) -> bool {
+        self.is_full()
+    }
+
+    /// Returns number of written elements.
+    #[inline]
+    pub fn len(&self) -> usize {
+        self.init
+    }
+
+    /// Returns number of elements left to write.
+    #[inline]
+    pub fn remaining(&self) -> usize {
+        N - self.init
+    }
+
+    /// Returns true if no elements were written.
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.init == 0
+    }
+
+    /// Returns slice of already written elements.
+    #[inline]
+    pub fn as_initialized_slice(&self) -> &[T] {
+        let slice = &self.array[..self.init];
+        unsafe {
+            // SAFETY: First `init` elements are initialized.
+            &*(slice as *const [MaybeUninit<T>] as *const [T])
+        }
+    }
+
+    /// Returns mutable slice of already written elements.
+    #[inline]
+    pub fn as_initialized_slice_mut(&mut self) -> &mut [T] {
+        let slice = &mut self.array[..self.init];
+        unsafe {
+            // SAFETY: First `init` elements are initialized.
+            &mut *(slice as *mut [MaybeUninit<T>] as *mut [T])
+        }
+    }
+
+    /// Returns boxed array without copying it.
+    ///
+    /// # Safety
+    ///
+    /// Must be called only when array is fully initialized.
+    /// Or equivalently, when `is_full` returns true.
+    #[inline]
+    pub unsafe fn assume_init(self) -> Box<[T; N]> {
+        debug_assert_eq!(self.init, N);
+        let this = ManuallyDrop::new(self);
+        let array = ptr::read(&this.array);
+
+        // SAFETY: Fully initialized. `MaybeUninit<T>` has the same layout as `T`.
+        Box::from_raw(Box::into_raw(array).cast::<[T; N]>())
+    }
+
+    /// Returns fully initialized boxed array.
+    /// Otherwise drops written elements and returns `None`.
+    #[inline]
+    pub fn try_init(self) -> Option<Box<[T; N]>> {
+        if self.init == N {
+            Some(unsafe {
+                // SAFETY: Fully initialized.
+                self.assume_init()
+            })
+        } else {
+            None
+        }
+    }
+}
+
+#[cfg(feature = "alloc")]
+impl<T, const N: usize> Drop for BoxedPartiallyInitArray<T, N> {
+    fn drop(&mut self) {
+        let slice = &mut self.array[..self.init];
+        unsafe { ptr::drop_in_place(slice as *mut [MaybeUninit<T>] as *mut [T]) }
+    }
+}
+
+#[cfg(feature = "alloc")]
+impl<T, const N: usize> fmt::Debug for BoxedPartiallyInitArray<T, N>
+where
+    T: fmt::Debug,
+{
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("BoxedPartiallyInitArray")
+            .field("initialized", &self.as_initialized_slice())
+            .field("remaining", &self.remaining())
+            .finish()
+    }
+}
+
+/// Heap-allocated counterpart of [`RevPartiallyInitArray`].
+#[cfg(feature = "alloc")]
+pub struct BoxedRevPartiallyInitArray<T, const N: usize> {
+    array: Box<[MaybeUninit<T>; N]>,
+    init: usize,
+}
+
+#[cfg(feature = "alloc")]
+impl<T, const N: usize> BoxedRevPartiallyInitArray<T, N> {
+    /// Returns empty builder.
+    #[inline]
+    pub fn uninit() -> Self {
+        BoxedRevPartiallyInitArray {
+            array: boxed_uninit_array(),
+            init: 0,
+        }
+    }
+
+    /// Writes element right before already written ones.
+    ///
+    /// # Safety
+    ///
+    /// Must be called at most `N` times.
+    /// Or equivalently, until `is_full` returns true.
+    #[inline]
+    pub unsafe fn write_back(&mut self, value: T) {
+        debug_assert!(self.init < N);
+        self.init += 1;
+        self.array[N - self.init].write(value);
+    }
+
+    /// Returns true if all elements are written.
+    #[inline]
+    pub fn is_full(&self) -> bool {
+        self.init == N
+    }
+
+    /// Returns number of written elements.
+    #[inline]
+    pub fn len(&self) -> usize {
+        self.init
+    }
+
+    /// Returns number of elements left to write.
+    #[inline]
+    pub fn remaining(&self) -> usize {
+        N - self.init
+    }
+
+    /// Returns true if no elements were written.
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.init == 0
+    }
+
+    /// Returns slice of already written elements in array order.
+    #[inline]
+    pub fn as_initialized_slice(&self) -> &[T] {
+        let slice = &self.array[N - self.init..];
+        unsafe {
+            // SAFETY: Last `init` elements are initialized.
+            &*(slice as *const [MaybeUninit<T>] as *const [T])
+        }
+    }
+
+    /// Returns mutable slice of already written elements in array order.
+    #[inline]
+    pub fn as_initialized_slice_mut(&mut self) -> &mut [T] {
+        let slice = &mut self.array[N - self.init..];
+        unsafe {
+            // SAFETY: Last `init` elements are initialized.
+            &mut *(slice as *mut [MaybeUninit<T>] as *mut [T])
+        }
+    }
+
+    /// Returns boxed array without copying it.
+    ///
+    /// # Safety
+    ///
+    /// Must be called only when array is fully initialized.
+    /// Or equivalently, when `is_full` returns true.
+    #[inline]
+    pub unsafe fn assume_init(self) -> Box<[T; N]> {
+        debug_assert_eq!(self.init, N);
+        let this = ManuallyDrop::new(self);
+        let array = ptr::read(&this.array);
+
+        // SAFETY: Fully initialized. `MaybeUninit<T>` has the same layout as `T`.
+        Box::from_raw(Box::into_raw(array).cast::<[T; N]>())
+    }
+
+    /// Returns fully initialized boxed array.
+    /// Otherwise drops written elements and returns `None`.
+    #[inline]
+    pub fn try_init(self) -> Option<Box<[T; N]>> {
+        if self.init == N {
+            Some(unsafe {
+                // SAFETY: Fully initialized.
+                self.assume_init()
+            })
+        } else {
+            None
+        }
+    }
+}
+
+#[cfg(feature = "alloc")]
+impl<T, const N: usize> Drop for BoxedRevPartiallyInitArray<T, N> {
+    fn drop(&mut self) {
+        let slice = &mut self.array[N - self.init..];
+        unsafe { ptr::drop_in_place(slice as *mut [MaybeUninit<T>] as *mut [T]) }
+    }
+}
+
+#[cfg(feature = "alloc")]
+impl<T, const N: usize> fmt::Debug for BoxedRevPartiallyInitArray<T, N>
+where
+    T: fmt::Debug,
+{
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("BoxedRevPartiallyInitArray")
+            .field("initialized", &self.as_initialized_slice())
+            .field("remaining", &self.remaining())
+            .finish()
+    }
+}
+
+/// Allocates uninitialized array on the heap.
+#[cfg(feature = "alloc")]
+fn boxed_uninit_array<T, const N: usize>() -> Box<[MaybeUninit<T>; N]> {
+    let layout = Layout::new::<[MaybeUninit<T>; N]>();
+    let ptr = if layout.size() == 0 {
+        NonNull::<[MaybeUninit<T>; N]>::dangling().as_ptr()
+    } else {
+        let ptr = unsafe {
+            // SAFETY: Layout has non-zero size.
+            alloc::alloc::alloc(layout)
+        };
+        if ptr.is_null() {
+            alloc::alloc::handle_alloc_error(layout);
+        }
+        ptr.cast()
+    };
+
+    unsafe {
+        // SAFETY: Pointer is allocated with global allocator for this layout
+        // or dangling for zero-sized layout.
+        // Array of `MaybeUninit` requires no initialization.
+        Box::from_raw(ptr)
+    }
+}
+
+/// Vector of length chosen at runtime initialized front-to-back.
+///
+/// Capacity for all elements is reserved up front.
+#[cfg(feature = "alloc")]
+pub struct PartiallyInitVec<T> {
+    vec: Vec<T>,
+    len: usize,
+}
+
+#[cfg(feature = "alloc")]
+impl<T> PartiallyInitVec<T> {
+    /// Returns empty builder for vector of `len` elements.
+    #[inline]
+    pub fn with_len(len: usize) -> Self {
+        PartiallyInitVec {
+            vec: Vec::with_capacity(len),
+            len,
+        }
+    }
+
+    /// Writes next element.
+    ///
+    /// # Safety
+    ///
+    /// Must be called at most `len` times.
+    /// Or equivalently, until `is_full` returns true.
+    ///
+    /// This method is unsafe only for symmetry with array builders.
+    #[inline]
+    pub unsafe fn write(&mut self, value: T) {
+        debug_assert!(self.vec.len() < self.len);
+        self.vec.push(value);
+    }
+
+    /// Returns true if all elements are written.
+    #[inline]
+    pub fn is_full(&self) -> bool {
+        self.vec.len() == self.len
+    }
+
+    /// Same as [`is_full`](Self::is_full).
+    #[deprecated(note = "use `is_full` instead")]
+    #[inline]
+    pub fn is_init(&self) -> bool {
+        self.is_full()
+    }
+
+    /// Returns number of written elements.
+    #[inline]
+    pub fn len(&self) -> usize {
+        self.vec.len()
+    }
+
+    /// Returns number of elements left to write.
+    #[inline]
+    pub fn remaining(&self) -> usize {
+        self.len - self.vec.len()
+    }
+
+    /// Returns true if no elements were written.
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.vec.is_empty()
+    }
+
+    /// Returns slice of already written elements.
+    #[inline]
+    pub fn as_initialized_slice(&self) -> &[T] {
+        &self.vec
+    }
+
+    /// Returns mutable slice of already written elements.
+    #[inline]
+    pub fn as_initialized_slice_mut(&mut self) -> &mut [T] {
+        &mut self.vec
+    }
+
+    /// Returns vector of written elements.
+    ///
+    /// # Safety
+    ///
+    /// Must be called only when vector is fully initialized.
+    /// Or equivalently, when `is_full` returns true.
+    #[inline]
+    pub unsafe fn assume_init(self) -> Vec<T> {
+        debug_assert_eq!(self.vec.len(), self.len);
+        self.vec
+    }
+
+    /// Returns fully initialized vector.
+    /// Otherwise drops written elements and returns `None`.
+    #[inline]
+    pub fn try_init(self) -> Option<Vec<T>> {
+        if self.is_full() {
+            Some(self.vec)
+        } else {
+            None
+        }
+    }
+}
+
+#[cfg(feature = "alloc")]
+impl<T> fmt::Debug for PartiallyInitVec<T>
+where
+    T: fmt::Debug,
+{
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("PartiallyInitVec")
+            .field("initialized", &self.as_initialized_slice())
+            .field("remaining", &self.remaining())
+            .finish()
+    }
+}
+
+/// Vector of length chosen at runtime initialized back-to-front.
+///
+/// Capacity for all elements is reserved up front.
+#[cfg(feature = "alloc")]
+pub struct RevPartiallyInitVec<T> {
+    vec: Vec<T>,
+    total: usize,
+    init: usize,
+}
+
+#[cfg(feature = "alloc")]
+impl<T> RevPartiallyInitVec<T> {
+    /// Returns empty builder for vector of `len` elements.
+    #[inline]
+    pub fn with_len(len: usize) -> Self {
+        RevPartiallyInitVec {
+            vec: Vec::with_capacity(len),
+            total: len,
+            init: 0,
+        }
+    }
+
+    /// Writes element right before already written ones.
+    ///
+    /// # Safety
+    ///
+    /// Must be called at most `len` times.
+    /// Or equivalently, until `is_full` returns true.
+    #[inline]
+    pub unsafe fn write_back(&mut self, value: T) {
+        debug_assert!(self.init < self.total);
+        self.init += 1;
+        // SAFETY: Capacity is at least `len`.
+        self.vec
+            .as_mut_ptr()
+            .add(self.total - self.init)
+            .write(value);
+    }
+
+    /// Returns true if all elements are written.
+    #[inline]
+    pub fn is_full(&self) -> bool {
+        self.init == self.total
+    }
+
+    /// Returns number of written elements.
+    #[inline]
+    pub fn len(&self) -> usize {
+        self.init
+    }
+
+    /// Returns number of elements left to write.
+    #[inline]
+    pub fn remaining(&self) -> usize {
+        self.total - self.init
+    }
+
+    /// Returns true if no elements were written.
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.init == 0
+    }
+
+    /// Returns slice of already written elements in vector order.
+    #[inline]
+    pub fn as_initialized_slice(&self) -> &[T] {
+        unsafe {
+            // SAFETY: Last `init` elements of reserved capacity are initialized.
+            slice::from_raw_parts(self.vec.as_ptr().add(self.total - self.init), self.init)
+        }
+    }
+
+    /// Returns mutable slice of already written elements in vector order.
+    #[inline]
+    pub fn as_initialized_slice_mut(&mut self) -> &mut [T] {
+        unsafe {
+            // SAFETY: Last `init` elements of reserved capacity are initialized.
+            slice::from_raw_parts_mut(self.vec.as_mut_ptr().add(self.total - self.init), self.init)
+        }
+    }
+
+    /// Returns vector of written elements.
+    ///
+    /// # Safety
+    ///
+    /// Must be called only when vector is fully initialized.
+    /// Or equivalently, when `is_full` returns true.
+    #[inline]
+    pub unsafe fn assume_init(mut self) -> Vec<T> {
+        debug_assert_eq!(self.init, self.total);
+        let mut vec = mem::take(&mut self.vec);
+        self.init = 0;
+
+        // SAFETY: All `len` elements are initialized.
+        vec.set_len(self.total);
+        vec
+    }
+
+    /// Returns fully initialized vector.
+    /// Otherwise drops written elements and returns `None`.
+    #[inline]
+    pub fn try_init(self) -> Option<Vec<T>> {
+        if self.is_full() {
+            Some(unsafe {
+                // SAFETY: Fully initialized.
+                self.assume_init()
+            })
+        } else {
+            None
+        }
+    }
+}
+
+#[cfg(feature = "alloc")]
+impl<T> Drop for RevPartiallyInitVec<T> {
+    fn drop(&mut self) {
+        let slice = self.as_initialized_slice_mut();
+        unsafe { ptr::drop_in_place(slice) }
+    }
+}
+
+#[cfg(feature = "alloc")]
+impl<T> fmt::Debug for RevPartiallyInitVec<T>
+where
+    T: fmt::Debug,
+{
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("RevPartiallyInitVec")
+            .field("initialized", &self.as_initialized_slice())
+            .field("remaining", &self.remaining())
+            .finish()
+    }
+}
@@ -0,0 +1,114 @@
+//! Collecting iterators into arrays with method chains.
+//!
+//! [`ArrayCollectExt`] is an alternative to [`collect_array!`](crate::collect_array)
+//! for plain iterators without patterns or predicates.
+
+use crate::{builder::PartiallyInitArray, ResultShunt};
+
+mod sealed {
+    pub trait Sealed {}
+
+    impl<I: Iterator> Sealed for I {}
+
+    pub trait SealedResult {}
+
+    impl<T, E> SealedResult for Result<T, E> {}
+}
+
+/// Items of fallible iterators accepted by [`ArrayCollectExt::collect_array_result`].
+///
+/// Implemented for [`Result`].
+pub trait ResultItem: sealed::SealedResult {
+    /// Type of successful value.
+    type Ok;
+
+    /// Type of error.
+    type Err;
+
+    /// Converts item into [`Result`].
+    fn into_result(self) -> Result<Self::Ok, Self::Err>;
+}
+
+impl<T, E> ResultItem for Result<T, E> {
+    type Ok = T;
+    type Err = E;
+
+    #[inline]
+    fn into_result(self) -> Result<T, E> {
+        self
+    }
+}
+
+/// Extension trait for collecting iterators into arrays.
+///
+/// Implemented for all iterators.
+///
+/// ```
+/// use array_fu::collect::ArrayCollectExt;
+///
+/// assert_eq!((1..).collect_array::<3>(), Some([1, 2, 3]));
+/// assert_eq!((1..3).collect_array::<3>(), None);
+/// assert_eq!((1..3).collect_array_or::<4>(0), [1, 2, 0, 0]);
+///
+/// let parsed = ["1", "2", "3"].iter().map(|s| s.parse::<u8>());
+/// assert_eq!(parsed.collect_array_result::<2>(), Ok(Some([1, 2])));
+///
+/// let parsed = ["1", "x", "3"].iter().map(|s| s.parse::<u8>());
+/// assert!(parsed.collect_array_result::<3>().is_err());
+/// ```
+pub trait ArrayCollectExt: Iterator + sealed::Sealed + Sized {
+    /// Collects first `N` elements into an array.
+    /// Returns `None` if iterator yields fewer than `N` elements.
+    ///
+    /// Iterator is not advanced once array is full.
+    #[inline]
+    fn collect_array<const N: usize>(self) -> Option<[Self::Item; N]> {
+        PartiallyInitArray::from_iter_checked(self)
+    }
+
+    /// Collects first `N` elements into an array.
+    /// Elements missing when iterator runs out are filled with clones of `default`.
+    ///
+    /// Iterator is not advanced once array is full.
+    #[inline]
+    fn collect_array_or<const N: usize>(self, default: Self::Item) -> [Self::Item; N]
+    where
+        Self::Item: Clone,
+    {
+        let mut array = PartiallyInitArray::uninit();
+        array.extend_from_iter(self);
+        if !array.is_full() {
+            array.fill_cloned(default);
+        }
+        unsafe {
+            // SAFETY: `fill_cloned` initializes all remaining elements.
+            array.assume_init()
+        }
+    }
+
+    /// Collects first `N` successful values into an array.
+    /// Returns first error, dropping values collected so far.
+    /// Returns `Ok(None)` if iterator yields fewer than `N` elements.
+    ///
+    /// Iterator is not advanced after first error or once array is full.
+    #[inline]
+    #[allow(clippy::type_complexity)]
+    fn collect_array_result<const N: usize>(
+        self,
+    ) -> Result<Option<[<Self::Item as ResultItem>::Ok; N]>, <Self::Item as ResultItem>::Err>
+    where
+        Self::Item: ResultItem,
+    {
+        let mut error = None;
+        let array = PartiallyInitArray::from_iter_checked(ResultShunt::new(
+            self.map(ResultItem::into_result),
+            &mut error,
+        ));
+        match error {
+            Some(err) => Err(err),
+            None => Ok(array),
+        }
+    }
+}
+
+impl<I: Iterator> ArrayCollectExt for I {}
@@ -0,0 +1,54 @@
+//! Iterators that never run out of values.
+//!
+//! [`EndlessIterator`] lets [`collect_array_endless!`](crate::collect_array_endless)
+//! return arrays without wrapping them into `Option`.
+
+use core::iter::{
+    Chain, Cloned, Copied, Cycle, Enumerate, Inspect, Map, Repeat, RepeatWith, Skip, StepBy, Zip,
+};
+use core::ops::RangeFrom;
+
+/// Iterator that never returns `None` from `next`.
+///
+/// Implemented for endless sources and adapters that preserve endlessness.
+/// [`Cycle`] is endless only when cycled iterator is not empty.
+/// [`RangeFrom`] panics or wraps around on overflow instead of ending.
+///
+/// This trait is a promise checked at runtime, unsafe code must not rely on it.
+pub trait EndlessIterator: Iterator {}
+
+impl<A> EndlessIterator for RangeFrom<A> where RangeFrom<A>: Iterator {}
+
+impl<A: Clone> EndlessIterator for Repeat<A> {}
+
+impl<A, F: FnMut() -> A> EndlessIterator for RepeatWith<F> {}
+
+impl<I: Iterator + Clone> EndlessIterator for Cycle<I> {}
+
+impl<B, I: EndlessIterator, F: FnMut(I::Item) -> B> EndlessIterator for Map<I, F> {}
+
+impl<I: EndlessIterator> EndlessIterator for Enumerate<I> {}
+
+impl<I: EndlessIterator, F: FnMut(&I::Item)> EndlessIterator for Inspect<I, F> {}
+
+impl<I: EndlessIterator> EndlessIterator for StepBy<I> {}
+
+impl<I: EndlessIterator> EndlessIterator for Skip<I> {}
+
+impl<A: EndlessIterator, B: EndlessIterator> EndlessIterator for Zip<A, B> {}
+
+impl<A: Iterator, B: EndlessIterator<Item = A::Item>> EndlessIterator for Chain<A, B> {}
+
+impl<'a, T: Clone + 'a, I: EndlessIterator<Item = &'a T>> EndlessIterator for Cloned<I> {}
+
+impl<'a, T: Copy + 'a, I: EndlessIterator<Item = &'a T>> EndlessIterator for Copied<I> {}
+
+#[doc(hidden)]
+#[inline]
+pub fn endless<I>(iter: I) -> I::IntoIter
+where
+    I: IntoIterator,
+    I::IntoIter: EndlessIterator,
+{
+    iter.into_iter()
+}
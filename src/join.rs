@@ -0,0 +1,91 @@
+//! Concurrent awaiting of arrays of futures.
+//!
+//! [`JoinArray`] is the future returned by [`join_array!`](crate::join_array).
+
+use core::{
+    future::Future,
+    mem,
+    pin::Pin,
+    task::{Context, Poll},
+};
+
+use crate::builder::PartiallyInitArray;
+
+enum MaybeDone<F: Future> {
+    Pending(F),
+    Done(F::Output),
+    Taken,
+}
+
+/// Future that polls array of futures concurrently
+/// and resolves to array of their outputs in the same order.
+///
+/// Futures and outputs are stored inline, no allocation is performed.
+/// Outputs of completed futures are dropped together with pending futures
+/// if `JoinArray` is dropped before completion.
+#[must_use = "futures do nothing unless you `.await` or poll them"]
+pub struct JoinArray<F: Future, const N: usize> {
+    slots: [MaybeDone<F>; N],
+}
+
+impl<F, const N: usize> JoinArray<F, N>
+where
+    F: Future,
+{
+    /// Returns future that joins all futures from the array.
+    #[inline]
+    pub fn new(futures: [F; N]) -> Self {
+        JoinArray {
+            slots: crate::array_map!(futures, f => MaybeDone::Pending(f)),
+        }
+    }
+}
+
+impl<F, const N: usize> Future for JoinArray<F, N>
+where
+    F: Future,
+{
+    type Output = [F::Output; N];
+
+    fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<[F::Output; N]> {
+        let slots = unsafe {
+            // SAFETY: Futures are never moved out of their slots.
+            &mut self.get_unchecked_mut().slots
+        };
+
+        let mut ready = true;
+        for slot in slots.iter_mut() {
+            if let MaybeDone::Pending(future) = slot {
+                let future = unsafe {
+                    // SAFETY: `JoinArray` is pinned, so are its slots.
+                    Pin::new_unchecked(future)
+                };
+
+                match future.poll(cx) {
+                    Poll::Ready(output) => *slot = MaybeDone::Done(output),
+                    Poll::Pending => ready = false,
+                }
+            }
+        }
+
+        if !ready {
+            return Poll::Pending;
+        }
+
+        let mut outputs = PartiallyInitArray::uninit();
+        for slot in slots {
+            match mem::replace(slot, MaybeDone::Taken) {
+                MaybeDone::Done(output) => unsafe {
+                    // SAFETY: There are exactly `N` slots.
+                    outputs.write(output)
+                },
+                _ => panic!("`JoinArray` polled after completion"),
+            }
+        }
+
+        Poll::Ready(unsafe {
+            // SAFETY: All slots were done.
+            outputs.assume_init()
+        })
+    }
+}
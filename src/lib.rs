@@ -43,18 +43,51 @@
 //!
 //! See more examples in the [`collect_array!`] macro documentation.
 //!
+//! Arrays can also be built step by step without macros using [`builder::PartiallyInitArray`].
+//!
 #![no_std]
 
+#[cfg(feature = "alloc")]
+extern crate alloc;
+
+#[cfg(feature = "alloc")]
+#[doc(hidden)]
+pub use alloc::vec::Vec as __Vec;
+
+#[cfg(test)]
+extern crate std;
+
+pub mod builder;
+pub mod collect;
+pub mod endless;
+
+#[cfg(feature = "futures")]
+pub mod join;
+
+#[cfg(feature = "rayon")]
+#[doc(hidden)]
+pub mod par;
+
 use core::{
-    mem::{self, MaybeUninit},
+    fmt,
+    ops::{Add, Sub},
     ptr,
 };
 
+use builder::PartiallyInitArray;
+
 #[doc(hidden)]
 pub type Usize = usize;
 
 #[doc(hidden)]
-pub use core::{iter::IntoIterator, num::Wrapping, ops::Not};
+pub use core::{
+    default::Default,
+    iter::{IntoIterator, Iterator},
+    marker::PhantomData,
+    mem::MaybeUninit,
+    num::Wrapping,
+    ops::Not,
+};
 
 #[doc(hidden)]
 pub struct DontBreakFromElementExpressionWithoutLabel;
@@ -64,502 +97,6136 @@ pub fn type_name_of_val<T: ?Sized>(_val: &T) -> &'static str {
     ::core::any::type_name::<T>()
 }
 
+/// Arrays and references to arrays that can be iterated
+/// alongside [`PartiallyInitArray`] of the same length.
 #[doc(hidden)]
-pub struct PartiallyInitArray<T, const N: usize> {
-    array: [MaybeUninit<T>; N],
-    init: usize,
-}
-
-impl<T, const N: usize> PartiallyInitArray<T, N> {
-    pub fn uninit() -> Self {
-        PartiallyInitArray {
-            // Could be written as `array![MaybeUninit::uninit(); N]`
-            array: unsafe {
-                // SAFETY: An uninitialized `[MaybeUninit<_>; N]` is valid.
-                MaybeUninit::uninit().assume_init()
-            },
-            init: 0,
-        }
+pub trait ArrayIter<const N: usize> {
+    type IntoIter: Iterator;
+
+    fn into_array_iter<U>(self, array: &PartiallyInitArray<U, N>) -> Self::IntoIter;
+}
+
+impl<T, const N: usize> ArrayIter<N> for [T; N] {
+    type IntoIter = core::array::IntoIter<T, N>;
+
+    #[inline]
+    fn into_array_iter<U>(self, _: &PartiallyInitArray<U, N>) -> Self::IntoIter {
+        IntoIterator::into_iter(self)
     }
+}
+
+impl<'a, T, const N: usize> ArrayIter<N> for &'a [T; N] {
+    type IntoIter = core::slice::Iter<'a, T>;
 
-    /// # Safety
-    ///
-    /// Must be called at most `N` times.
-    /// Or equivalently, until `is_init` returns false.
     #[inline]
-    pub unsafe fn write(&mut self, value: T) {
-        debug_assert!(self.init < N);
-        self.array[self.init].write(value);
-        self.init += 1;
+    fn into_array_iter<U>(self, _: &PartiallyInitArray<U, N>) -> Self::IntoIter {
+        self.iter()
     }
+}
+
+impl<'a, T, const N: usize> ArrayIter<N> for &'a mut [T; N] {
+    type IntoIter = core::slice::IterMut<'a, T>;
 
     #[inline]
-    pub fn is_init(&self) -> bool {
-        self.init == N
+    fn into_array_iter<U>(self, _: &PartiallyInitArray<U, N>) -> Self::IntoIter {
+        self.iter_mut()
     }
+}
 
-    /// # Safety
-    ///
-    /// Must be called after `write` was called exactly `N` times.
-    /// Or equivalently, when `is_init` returns true.
+#[doc(hidden)]
+#[inline]
+pub fn map_builder<T, U, const N: usize>(_: &[T; N]) -> PartiallyInitArray<U, N> {
+    PartiallyInitArray::uninit()
+}
+
+#[doc(hidden)]
+#[inline]
+pub fn unzip_builders<T, U, const N: usize>(
+    _: &[(T, U); N],
+) -> (PartiallyInitArray<T, N>, PartiallyInitArray<U, N>) {
+    (PartiallyInitArray::uninit(), PartiallyInitArray::uninit())
+}
+
+/// Number of listed elements preceding fill expression.
+#[doc(hidden)]
+pub struct ListLen<const K: usize>;
+
+struct ListFits<const K: usize, const N: usize>;
+
+impl<const K: usize, const N: usize> ListFits<K, N> {
+    const OK: () = assert!(K <= N, "Too many listed elements for array length");
+}
+
+impl<const K: usize> ListLen<K> {
+    /// Takes length of spread array from its type.
     #[inline]
-    pub unsafe fn assume_init(self) -> [T; N] {
-        debug_assert_eq!(self.init, N);
-        let array = {
-            // SAFETY: Fully initialized.
-            mem::transmute_copy::<[MaybeUninit<T>; N], [T; N]>(&self.array)
-        };
-        mem::forget(self);
-        array
+    pub fn of<T>(_: &[T; K]) -> Self {
+        ListLen
     }
 
     #[inline]
-    pub fn try_init(self) -> Option<[T; N]> {
-        if self.init == N {
-            let array = unsafe {
-                // SAFETY: Fully initialized.
-                mem::transmute_copy::<[MaybeUninit<T>; N], [T; N]>(&self.array)
-            };
-            mem::forget(self);
-            Some(array)
-        } else {
-            None
-        }
+    pub fn check<T, const N: usize>(self, _: &PartiallyInitArray<T, N>) {
+        ListFits::<K, N>::OK
     }
-}
 
-impl<T, const N: usize> Drop for PartiallyInitArray<T, N> {
-    fn drop(&mut self) {
-        let slice = &mut self.array[..self.init];
-        unsafe { ptr::drop_in_place(slice as *mut [MaybeUninit<T>] as *mut [T]) }
+    #[cfg(feature = "alloc")]
+    #[inline]
+    pub fn check_boxed<T, const N: usize>(self, _: &builder::BoxedPartiallyInitArray<T, N>) {
+        ListFits::<K, N>::OK
     }
-}
 
-#[doc(hidden)]
-#[macro_export]
-macro_rules! pattern_list {
-    ($ph:pat, $($pt:pat,)*) => {
-        $crate::pattern_list!($($pt,)* ; $ph )
-    };
-    ($ph:pat, $($pt:pat,)* ; $r:pat) => {
-        $crate::pattern_list!($($pt,)* ; ($r, $ph) )
-    };
-    (; $r:pat) => {
-        $r
-    };
+    #[cfg(feature = "alloc")]
+    #[inline]
+    #[track_caller]
+    pub fn check_vec<T>(self, vec: &builder::PartiallyInitVec<T>) {
+        assert!(
+            K <= vec.remaining(),
+            "Too many listed elements for vector length, {} listed for {} elements",
+            K,
+            vec.remaining()
+        );
+    }
 }
 
-/// Constructs arrays by repeating expression execution,
-/// possibly with enumeration bound to provided pattern.
-///
-/// # Syntax
-///
-/// On the basic level, arrays construction happens by repeating execution of provided expression multiple times.
-/// Note that the expression itself appears exactly once in expanded code.
-/// And length expression is executed in const context exactly once.
-///
-/// ```
-/// # use array_fu::array;
-/// let values = array![1; 2];
-///
-/// assert_eq!(values, [1, 1]);
-/// ```
-///
-/// Unlike built-in syntax `[$expr; $size]` `array!` runs expression `$size` times instead of copying result.
-/// This means that expression will exhibit its side effects for each array element,
-/// and value can change freely.
-///
-/// ```
-/// # use array_fu::array;
-/// # use rand::random;
-/// let values: [f32; 2] = array![random(); 2];
-/// ```
-///
-/// This also means that expression type may not be `Copy` or event `Clone`.
-///
-/// ```
-/// # use array_fu::array;
-/// # use std::sync::Mutex;
-/// let values = array![Mutex::new(1); 2];
-/// ```
-///
-/// ## Enumerate
-///  
-/// `array!` macro supports enumerating while constructing array elements.
-///
-/// `array!($pat => $expr ; $n)` does the trick. That's it, simply add `$pat =>` before element expression.
-///
-/// `$pat` must be valid pattern. And it will be bound to numbers starting from 0.
-/// Bound value can be used in the element expression.
-///
-/// ```
-/// # use array_fu::array;
-/// let values = array![x => x + 1; 3];
-///
-/// assert_eq!(values, [1, 2, 3]);
-/// ```
-///
-/// ## Predicates
-///
-/// `array!` macro supports predicated that are evaluated before element expression for each constructed element.
-/// When predicate does not pass, element expression is not executed.
-/// Value bound to pattern will be updated before trying again.
-///
-/// ```
-/// # use array_fu::array;
-/// let values = array![x => x + 1; where x & 1 == 1; 3];
-///
-/// assert_eq!(values, [2, 4, 6]);
-/// ```
-///
-/// It is possible to make array expression infeasible.
-/// For example by providing predicate that never evaluates to true.
-///
-/// ```should_panic
-/// # use array_fu::array;
-///
-/// // predicate always evaluates to `false`
-/// // making it impossible to construct array of size 1 or greater.
-/// // This will lead to a panic with descriptive message.
-/// // `[u8; 1]` type forces enumerator to be `u8` allowing it to fail faster.
-/// let _: [u8; 1] = array![x => x; where false; 1];
-/// ```
-///
-/// ## Control flow
-///
-/// Element expressions and conditions are executed in the inner loop scope but in the outer function.
-/// This makes it possible to perform early return from macro invocation using `return` and `break` and `continue` statements.
-/// `continue` and `break` won't compile without a label. If label is provided, they will behave as expected.
-/// `return` would exit function where macro is called.
-/// If size of the array is `0`, element and condition expressions won't be executed even once
-/// and `return` statement won't exit the function.
-/// This behavior is different from `[return; 0]` which performs early return regardless.
+/// Returns array of uninitialized elements.
 ///
 /// ```
-/// # use array_fu::array;
-/// array![return; 1];
-/// ```
-///
-/// ```compile_fail
-/// # use array_fu::array;
-/// array![break; 1];
-/// ```
+/// # use array_fu::uninit_array;
+/// let mut array = uninit_array::<u32, 2>();
+/// array[0].write(1);
+/// array[1].write(2);
 ///
-/// ```compile_fail
-/// # use array_fu::array;
-/// array![continue; 1];
-/// ```
-///
-/// ```
-/// # use array_fu::array;
-/// 'a: loop { array![break 'a; 1]; };
+/// assert_eq!(unsafe { array_fu::assume_init_array(array) }, [1, 2]);
 /// ```
+#[inline]
+pub const fn uninit_array<T, const N: usize>() -> [MaybeUninit<T>; N] {
+    unsafe {
+        // SAFETY: An uninitialized `[MaybeUninit<_>; N]` is valid.
+        MaybeUninit::uninit().assume_init()
+    }
+}
+
+/// Converts array of initialized `MaybeUninit` elements into array of values.
 ///
-/// ```
-/// # use array_fu::array;
-/// 'a: for _ in 0..3 { array![continue 'a; 1]; };
-/// ```
+/// # Safety
 ///
-/// ## List
+/// All elements of the array must be initialized.
+#[inline]
+pub const unsafe fn assume_init_array<T, const N: usize>(array: [MaybeUninit<T>; N]) -> [T; N] {
+    // SAFETY: `MaybeUninit<T>` has the same layout as `T`
+    // and `MaybeUninit` never drops its content, so `array` is not used after the read.
+    ptr::read(&array as *const [MaybeUninit<T>; N] as *const [T; N])
+}
+
+/// Reverses order of array elements in place.
 ///
-/// For consistency with built-in syntax, arrays may be constructed with a list of expressions.
+/// Elements are swapped, neither `Clone` nor `Copy` is required.
 ///
 /// ```
-/// # use array_fu::array;
-/// let values = array![1, 2, 3];
+/// # use array_fu::array_rev;
+/// let values = array_rev([String::from("a"), String::from("b"), String::from("c")]);
 ///
-/// assert_eq!(values, [1, 2, 3]);
+/// assert_eq!(values, ["c", "b", "a"]);
 /// ```
-#[macro_export]
-macro_rules! array {
-    ($($e:expr),* $(,)?) => { [$($e,)*] };
-
-    ($e:expr; $n:expr) => {{
-        $crate::array!( _ => $e ; $n )
-    }};
-
-    ($p:pat => $e:expr $( ; where $( $cond:expr ),+ )? ; $n:expr) => {{
-        #[allow(unused_mut)]
-        let mut array = $crate::PartiallyInitArray::<_, $n>::uninit();
+#[inline]
+pub fn array_rev<T, const N: usize>(mut array: [T; N]) -> [T; N] {
+    array.reverse();
+    array
+}
 
-        let mut i = $crate::Wrapping(0);
-        loop {
-            let value = i.0;
-            i += 1;
+#[doc(hidden)]
+#[inline]
+#[track_caller]
+pub fn slice_array<T: Copy, const N: usize>(slice: &[T]) -> [T; N] {
+    match <[T; N]>::try_from(slice) {
+        Ok(array) => array,
+        Err(_) => panic!(
+            "Failed to initialize array of {} elements from slice of {} elements",
+            N,
+            slice.len()
+        ),
+    }
+}
 
-            if i.0 == 0 {
-                panic!("Failed to initialize array using whole '{}' space", $crate::type_name_of_val(&i.0));
-            }
+#[doc(hidden)]
+#[inline]
+#[track_caller]
+pub fn concat_extend<T, I, const N: usize>(array: &mut PartiallyInitArray<T, N>, iter: I)
+where
+    I: IntoIterator<Item = T>,
+{
+    for elem in iter {
+        if array.push(elem).is_err() {
+            panic!(
+                "Failed to concatenate arrays into array of {} elements, too many elements provided",
+                N
+            )
+        }
+    }
+}
 
-            if array.is_init() {
-                // This is the only way ouf of the loop without leaving outer scope.
-                break;
-            }
+#[doc(hidden)]
+#[inline]
+#[track_caller]
+pub fn concat_finish<T, const N: usize>(array: PartiallyInitArray<T, N>) -> [T; N] {
+    let len = array.len();
+    match array.try_init() {
+        Some(array) => array,
+        None => panic!(
+            "Failed to concatenate arrays into array of {} elements, only {} elements provided",
+            N, len
+        ),
+    }
+}
 
-            match value {
-                $p => {
-                    #[allow(unreachable_code)]
-                    {
-                        $($(
-                            #[allow(unused_variables)]
-                            #[warn(unreachable_code)]
-                            let cond = $cond;
+#[doc(hidden)]
+#[inline]
+#[track_caller]
+pub fn collect_exactly<I, const N: usize>(mut iter: I) -> [I::Item; N]
+where
+    I: Iterator,
+{
+    let mut array = PartiallyInitArray::uninit();
+    while !array.is_full() {
+        match iter.next() {
+            Some(value) => unsafe {
+                // SAFETY: Not fully initialized.
+                array.write(value)
+            },
+            None => panic!(
+                "Failed to collect exactly {} elements, iterator yielded only {} elements",
+                N,
+                array.len()
+            ),
+        }
+    }
 
-                            if <bool as $crate::Not>::not(cond) { continue; }
-                        )+)?
+    if iter.next().is_some() {
+        panic!(
+            "Failed to collect exactly {} elements, iterator yielded more elements",
+            N
+        );
+    }
 
-                        #[allow(unused_variables)]
-                        let elem;
+    unsafe {
+        // SAFETY: Loop above exits only when array is fully initialized.
+        array.assume_init()
+    }
+}
 
-                        #[allow(unused_variables)]
-                        let dont_continue_in_element_expression_without_label;
+#[doc(hidden)]
+#[inline]
+#[track_caller]
+pub fn array_from_iter<I, const N: usize>(mut iter: I) -> [I::Item; N]
+where
+    I: Iterator,
+{
+    let mut array = PartiallyInitArray::uninit();
+    array.extend_from_iter(&mut iter);
 
-                        loop {
-                            #[allow(unused)]
-                            {
-                                dont_continue_in_element_expression_without_label = ();
-                            }
+    let len = array.len();
+    match array.try_init() {
+        Some(array) => array,
+        None => panic!(
+            "Failed to take {} elements of type '{}' from iterator, only {} available",
+            N,
+            ::core::any::type_name::<I::Item>(),
+            len
+        ),
+    }
+}
 
-                            #[allow(unused_variables)]
-                            #[warn(unreachable_code)]
-                            let value = $e;
+#[doc(hidden)]
+#[inline]
+#[track_caller]
+pub fn array_windows<I, const W: usize, const K: usize>(mut iter: I) -> Option<[[I::Item; W]; K]>
+where
+    I: Iterator,
+    I::Item: Clone,
+{
+    assert!(W != 0, "Window size must be non-zero");
 
-                            elem = value;
+    let mut windows = PartiallyInitArray::<[I::Item; W], K>::uninit();
+    if windows.is_full() {
+        return windows.try_init();
+    }
 
-                            break $crate::DontBreakFromElementExpressionWithoutLabel;
-                        };
+    let mut window = PartiallyInitArray::<I::Item, W>::uninit();
+    window.extend_from_iter(&mut iter);
+    let mut window = window.try_init()?;
 
-                        unsafe {
-                            array.write(elem);
-                        }
-                    }
-                }
-                #[allow(unreachable_patterns)]
-                _ => continue,
+    loop {
+        if windows.remaining() == 1 {
+            // Last window is moved instead of cloned.
+            unsafe {
+                // SAFETY: Not fully initialized.
+                windows.write(window);
             }
+            return windows.try_init();
         }
 
         unsafe {
-            // SAFETY: `is_init` returned true.
-            array.assume_init()
+            // SAFETY: Not fully initialized.
+            windows.write(window.clone());
         }
-    }};
+
+        window.rotate_left(1);
+        window[W - 1] = iter.next()?;
+    }
 }
 
-/// Constructs arrays by repeating expression
-/// with elements from iterators bound to provided patterns.
-///
-/// Creating arrays from iterators is really handy.
-/// But it comes at price - there could be not enough values in the iterator to fill the array.
-///
-/// Therefore this macro returns `Option`.
-/// `Some` array is returned if there were enough values.
-/// Otherwise `None` is returned.
-///
-/// ```
-/// # use array_fu::collect_array;
-/// let opt = collect_array![1..; 3];
+/// Window size of [`array_windows!`] over array reference.
+#[doc(hidden)]
+pub struct WindowLen<const W: usize>;
+
+struct WindowsFit<const M: usize, const W: usize, const K: usize>;
+
+impl<const M: usize, const W: usize, const K: usize> WindowsFit<M, W, K> {
+    const OK: () = assert!(
+        W != 0 && W <= M && K == M - W + 1,
+        "Failed to split array into windows, there must be exactly `array length - window size + 1` non-empty windows"
+    );
+}
+
+#[doc(hidden)]
+#[inline]
+pub fn array_ref_windows<T, const M: usize, const W: usize, const K: usize>(
+    array: &[T; M],
+    _: WindowLen<W>,
+) -> [[&T; W]; K] {
+    let () = WindowsFit::<M, W, K>::OK;
+    array![i => array![j => &array[i + j]; W]; K]
+}
+
+/// Chunk size of [`array_chunks!`] over array reference.
+#[doc(hidden)]
+pub struct ChunkLen<const C: usize>;
+
+struct ChunksFit<const M: usize, const C: usize, const K: usize>;
+
+impl<const M: usize, const C: usize, const K: usize> ChunksFit<M, C, K> {
+    const OK: () = assert!(
+        C != 0 && K == M / C,
+        "Failed to split array into chunks, there must be exactly `array length / chunk size` non-empty chunks"
+    );
+}
+
+#[doc(hidden)]
+#[inline]
+pub fn array_ref_chunks<T, const M: usize, const C: usize, const K: usize>(
+    array: &[T; M],
+    _: ChunkLen<C>,
+) -> ([&[T; C]; K], &[T]) {
+    let () = ChunksFit::<M, C, K>::OK;
+    let chunks = array![i => match <&[T; C]>::try_from(&array[i * C..][..C]) {
+        Ok(chunk) => chunk,
+        Err(_) => unreachable!(),
+    }; K];
+    (chunks, &array[K * C..])
+}
+
+#[doc(hidden)]
+#[inline]
+pub fn array_chunks<I, const C: usize, const K: usize>(mut iter: I) -> Option<[[I::Item; C]; K]>
+where
+    I: Iterator,
+{
+    let mut chunks = PartiallyInitArray::<[I::Item; C], K>::uninit();
+    chunks
+        .fill_with_result(|| PartiallyInitArray::from_iter_checked(&mut iter).ok_or(()))
+        .ok()?;
+    chunks.try_init()
+}
+
+struct SplitFits<const N: usize, const M: usize, const K: usize>;
+
+impl<const N: usize, const M: usize, const K: usize> SplitFits<N, M, K> {
+    const OK: () = assert!(
+        matches!(M.checked_add(K), Some(len) if len == N),
+        "Failed to split array, lengths of parts must add up to the length of the source array"
+    );
+}
+
+#[doc(hidden)]
+#[inline]
+pub fn split_array<T, const N: usize, const M: usize, const K: usize>(
+    array: [T; N],
+) -> ([T; M], [T; K]) {
+    let () = SplitFits::<N, M, K>::OK;
+
+    let mut iter = IntoIterator::into_iter(array);
+    let mut left = PartiallyInitArray::<T, M>::uninit();
+    left.extend_from_iter(&mut iter);
+    let mut right = PartiallyInitArray::<T, K>::uninit();
+    right.extend_from_iter(&mut iter);
+
+    unsafe {
+        // SAFETY: Source array has exactly `M + K` elements.
+        (left.assume_init(), right.assume_init())
+    }
+}
+
+struct FlattenFits<const M: usize, const N: usize, const K: usize>;
+
+impl<const M: usize, const N: usize, const K: usize> FlattenFits<M, N, K> {
+    const OK: () = assert!(
+        matches!(M.checked_mul(N), Some(len) if len == K),
+        "Failed to flatten arrays, length of the result must be equal to the total number of elements"
+    );
+}
+
+#[doc(hidden)]
+#[inline]
+pub fn flatten_array<T, const M: usize, const N: usize, const K: usize>(
+    array: [[T; M]; N],
+) -> [T; K] {
+    let () = FlattenFits::<M, N, K>::OK;
+
+    let mut flat = PartiallyInitArray::<T, K>::uninit();
+    for inner in array {
+        flat.extend_from_iter(inner);
+    }
+
+    unsafe {
+        // SAFETY: Source arrays have exactly `K` elements in total.
+        flat.assume_init()
+    }
+}
+
+#[doc(hidden)]
+pub struct Enumerator<T> {
+    start: Wrapping<T>,
+    step: Wrapping<T>,
+    next: Wrapping<T>,
+    descending: bool,
+    reversed: bool,
+    exhausted: bool,
+    tries: Option<usize>,
+    fill_rev: bool,
+    positions: Option<usize>,
+}
+
+impl<T> Enumerator<T>
+where
+    T: Copy,
+{
+    #[inline]
+    pub fn new(start: T, step: T) -> Self {
+        Enumerator {
+            start: Wrapping(start),
+            step: Wrapping(step),
+            next: Wrapping(start),
+            descending: false,
+            reversed: false,
+            exhausted: false,
+            tries: None,
+            fill_rev: false,
+            positions: None,
+        }
+    }
+
+    /// Pins enumerated values type.
+    #[inline]
+    pub fn of_type(self, _: PhantomData<T>) -> Self {
+        self
+    }
+
+    /// Sets value the enumeration starts from.
+    #[inline]
+    pub fn from(mut self, start: T) -> Self {
+        self.start = Wrapping(start);
+        self.next = Wrapping(start);
+        self
+    }
+
+    /// Sets value the enumeration starts from and counts down.
+    #[inline]
+    pub fn down_from(mut self, start: T) -> Self {
+        self.descending = true;
+        self.from(start)
+    }
+
+    /// Sets value the enumerator advances by on each step.
+    #[inline]
+    pub fn by(mut self, step: T) -> Self {
+        self.step = Wrapping(step);
+        self
+    }
+
+    /// Reverses order of the enumeration.
+    #[inline]
+    pub fn reversed(mut self) -> Self {
+        self.reversed = !self.reversed;
+        self
+    }
+
+    /// Limits number of values this enumerator yields.
+    #[inline]
+    pub fn tries(mut self, tries: usize) -> Self {
+        self.tries = Some(tries);
+        self
+    }
+
+    /// Reverses order of the enumeration and limits it to one value per array element.
+    #[inline]
+    pub fn fill_rev(mut self) -> Self {
+        self.fill_rev = true;
+        self.reversed()
+    }
+
+    #[inline]
+    pub fn type_name(&self) -> &'static str {
+        ::core::any::type_name::<T>()
+    }
+
+    /// Panics with message describing why enumerator failed to fill the array
+    /// with `len` of `n` elements initialized.
+    /// Stringified predicates are included in the message.
+    #[cold]
+    #[track_caller]
+    pub fn exhausted(&self, len: usize, n: usize, predicates: &[&str]) -> ! {
+        if self.exhausted {
+            panic!(
+                "Failed to initialize array using whole '{}' space, {} of {} elements initialized{}",
+                self.type_name(),
+                len,
+                n,
+                Predicates(predicates),
+            )
+        } else if self.positions == Some(0) {
+            panic!(
+                "Failed to initialize array back-to-front using one value per element, {} of {} elements initialized{}",
+                len,
+                n,
+                Predicates(predicates),
+            )
+        } else {
+            panic!(
+                "Failed to initialize array within attempt budget, {} of {} elements initialized{}",
+                len,
+                n,
+                Predicates(predicates),
+            )
+        }
+    }
+}
+
+/// Formats stringified predicates for panic messages.
+struct Predicates<'a>(&'a [&'a str]);
+
+impl fmt::Display for Predicates<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.0 {
+            [] => Ok(()),
+            [predicate] => write!(f, "; predicate: `{}`", predicate),
+            [first, rest @ ..] => {
+                write!(f, "; predicates: `{}`", first)?;
+                for predicate in rest {
+                    write!(f, ", `{}`", predicate)?;
+                }
+                Ok(())
+            }
+        }
+    }
+}
+
+impl<T> Enumerator<T>
+where
+    T: Copy + PartialEq,
+    Wrapping<T>: Add<Output = Wrapping<T>> + Sub<Output = Wrapping<T>>,
+{
+    /// Prepares enumerator to fill array of `len` elements.
+    ///
+    /// Reversed enumerator starts from the value that would be the last one
+    /// and counts in opposite direction.
+    /// With `fill rev` it stops after `len` values.
+    ///
+    /// Panics if step is zero.
+    #[inline]
+    #[track_caller]
+    pub fn prepare(&mut self, len: usize) {
+        assert!(
+            self.start + self.step != self.start,
+            "Enumeration step must be non-zero"
+        );
+
+        if self.reversed {
+            for _ in 1..len {
+                self.advance();
+            }
+            self.start = self.next;
+            self.descending = !self.descending;
+            self.reversed = false;
+        }
+
+        if self.fill_rev {
+            self.positions = Some(len);
+        }
+    }
+
+    /// Enumerator has no fixed number of values to match array length.
+    #[inline]
+    pub fn exact(&self, _len: usize) {}
+
+    #[inline]
+    fn advance(&mut self) {
+        if self.descending {
+            self.next = self.next - self.step;
+        } else {
+            self.next = self.next + self.step;
+        }
+    }
+}
+
+impl<T> Iterator for Enumerator<T>
+where
+    T: Copy + PartialEq,
+    Wrapping<T>: Add<Output = Wrapping<T>> + Sub<Output = Wrapping<T>>,
+{
+    type Item = T;
+
+    /// Returns next value until enumerator wraps around to the start.
+    #[inline]
+    fn next(&mut self) -> Option<T> {
+        if self.exhausted {
+            return None;
+        }
+
+        if self.positions == Some(0) || self.tries == Some(0) {
+            return None;
+        }
+
+        if let Some(positions) = &mut self.positions {
+            *positions -= 1;
+        }
+
+        if let Some(tries) = &mut self.tries {
+            *tries -= 1;
+        }
+
+        let value = self.next;
+        self.advance();
+        self.exhausted = self.next == self.start;
+        Some(value.0)
+    }
+}
+
+/// Enumerates pairs of row and column indices in row-major order.
+#[doc(hidden)]
+pub struct Grid {
+    rows: usize,
+    cols: usize,
+    next: usize,
+    end: usize,
+    reversed: bool,
+    tries: Option<usize>,
+}
+
+impl Enumerator<usize> {
+    /// Turns enumerator into grid of `rows` by `cols` pairs.
+    #[inline]
+    #[track_caller]
+    pub fn over(self, rows: usize, cols: usize) -> Grid {
+        let end = match rows.checked_mul(cols) {
+            Some(end) => end,
+            None => panic!("Grid of {} by {} pairs is too large", rows, cols),
+        };
+
+        Grid {
+            rows,
+            cols,
+            next: 0,
+            end,
+            reversed: false,
+            tries: None,
+        }
+    }
+}
+
+impl Grid {
+    /// Reverses order of the enumeration.
+    #[inline]
+    pub fn reversed(mut self) -> Self {
+        self.reversed = !self.reversed;
+        self
+    }
+
+    /// Limits number of pairs this grid yields.
+    #[inline]
+    pub fn tries(mut self, tries: usize) -> Self {
+        self.tries = Some(tries);
+        self
+    }
+
+    /// Reverses order of the enumeration, grid already yields each pair once.
+    #[inline]
+    pub fn fill_rev(self) -> Self {
+        self.reversed()
+    }
+
+    /// Grid does not depend on array length.
+    #[inline]
+    pub fn prepare(&mut self, _len: usize) {}
+
+    /// Checks that array of `len` elements took every pair.
+    /// Attempt budget allows using fewer pairs.
+    #[inline]
+    #[track_caller]
+    pub fn exact(&self, len: usize) {
+        if self.tries.is_none() && self.next != self.end {
+            panic!(
+                "Failed to initialize array of {} elements using {} by {} pairs without predicates",
+                len, self.rows, self.cols,
+            )
+        }
+    }
+
+    /// Panics with message describing why grid failed to fill the array
+    /// with `len` of `n` elements initialized.
+    #[cold]
+    #[track_caller]
+    pub fn exhausted(&self, len: usize, n: usize, predicates: &[&str]) -> ! {
+        if self.next == self.end {
+            panic!(
+                "Failed to initialize array using all {} by {} pairs, {} of {} elements initialized{}",
+                self.rows,
+                self.cols,
+                len,
+                n,
+                Predicates(predicates),
+            )
+        } else {
+            panic!(
+                "Failed to initialize array within attempt budget, {} of {} elements initialized{}",
+                len,
+                n,
+                Predicates(predicates),
+            )
+        }
+    }
+}
+
+impl Iterator for Grid {
+    type Item = (usize, usize);
+
+    #[inline]
+    fn next(&mut self) -> Option<(usize, usize)> {
+        if self.next == self.end {
+            return None;
+        }
+
+        if let Some(tries) = &mut self.tries {
+            if *tries == 0 {
+                return None;
+            }
+            *tries -= 1;
+        }
+
+        let index = if self.reversed {
+            self.end - 1 - self.next
+        } else {
+            self.next
+        };
+        self.next += 1;
+
+        Some((index / self.cols, index % self.cols))
+    }
+}
+
+/// Iterator adapter that yields `Ok` values and stops on first `Err`, stashing it.
+#[doc(hidden)]
+pub struct ResultShunt<'a, I, E> {
+    iter: I,
+    error: &'a mut Option<E>,
+}
+
+impl<'a, I, T, E> ResultShunt<'a, I, E>
+where
+    I: Iterator<Item = Result<T, E>>,
+{
+    #[inline]
+    pub fn new(iter: impl IntoIterator<IntoIter = I>, error: &'a mut Option<E>) -> Self {
+        ResultShunt {
+            iter: iter.into_iter(),
+            error,
+        }
+    }
+}
+
+impl<I, T, E> Iterator for ResultShunt<'_, I, E>
+where
+    I: Iterator<Item = Result<T, E>>,
+{
+    type Item = T;
+
+    #[inline]
+    fn next(&mut self) -> Option<T> {
+        if self.error.is_some() {
+            return None;
+        }
+
+        match self.iter.next()? {
+            Ok(value) => Some(value),
+            Err(err) => {
+                *self.error = Some(err);
+                None
+            }
+        }
+    }
+}
+
+// Nests patterns left-associatively to match items of chained `zip`,
+// `a, b, c` becomes `((a, b), c)`.
+#[doc(hidden)]
+#[macro_export]
+macro_rules! pattern_list {
+    ($ph:pat, $($pt:pat,)*) => {
+        $crate::pattern_list!($($pt,)* ; $ph )
+    };
+    ($ph:pat, $($pt:pat,)* ; $r:pat) => {
+        $crate::pattern_list!($($pt,)* ; ($r, $ph) )
+    };
+    (; $r:pat) => {
+        $r
+    };
+}
+
+/// Constructs arrays by repeating expression execution,
+/// possibly with enumeration bound to provided pattern.
 ///
-/// assert_eq!(opt, Some([1, 2, 3]));
-/// ```
+/// # Syntax
 ///
-/// `None` is returned otherwise.
+/// On the basic level, arrays construction happens by repeating execution of provided expression multiple times.
+/// Note that the expression itself appears exactly once in expanded code.
+/// And length expression is executed in const context exactly once.
 ///
 /// ```
-/// # use array_fu::collect_array;
-/// let opt = collect_array![1..3; 3];
+/// # use array_fu::array;
+/// let values = array![1; 2];
 ///
-/// assert_eq!(opt, None, "There's only two elements in 1..3");
+/// assert_eq!(values, [1, 1]);
 /// ```
 ///
-/// Similarly to `array!` macro, `collect_array` can be given a pattern to bind iterator elements
-/// and expression to produce array elements.
+/// Unlike built-in syntax `[$expr; $size]` `array!` runs expression `$size` times instead of copying result.
+/// This means that expression will exhibit its side effects for each array element,
+/// and value can change freely.
 ///
 /// ```
-/// # use array_fu::collect_array;
-/// let opt = collect_array![x in 1.. => x / 2; 3];
-///
-/// assert_eq!(opt, Some([0, 1, 1]));
+/// # use array_fu::array;
+/// # use rand::random;
+/// let values: [f32; 2] = array![random(); 2];
 /// ```
 ///
-/// But why stop there? Multiple iterators can be collected into an array!
+/// This also means that expression type may not be `Copy` or event `Clone`.
 ///
 /// ```
-/// # use array_fu::collect_array;
-/// let opt = collect_array![x in 1.., y in 2.. => x + y; 3];
-///
-/// assert_eq!(opt, Some([3, 5, 7]));
+/// # use array_fu::array;
+/// # use std::sync::Mutex;
+/// let values = array![Mutex::new(1); 2];
 /// ```
 ///
-/// Surely it also supports predicates.
-/// When predicate evaluates to `false`, next items are taken from all iterators.
+/// ## Enumerate
+///  
+/// `array!` macro supports enumerating while constructing array elements.
+///
+/// `array!($pat => $expr ; $n)` does the trick. That's it, simply add `$pat =>` before element expression.
+///
+/// `$pat` must be valid pattern. And it will be bound to numbers starting from 0.
+/// Bound value can be used in the element expression.
 ///
 /// ```
-/// # use array_fu::collect_array;
-/// let opt = collect_array![x in 1.., y in 2.. => x + y; where x * y > 10; 3];
+/// # use array_fu::array;
+/// let values = array![x => x + 1; 3];
 ///
-/// assert_eq!(opt, Some([7, 9, 11]));
+/// assert_eq!(values, [1, 2, 3]);
 /// ```
 ///
-/// Patterns support destructuring.
+/// Enumerator type is inferred from the pattern and element expression usage.
+/// It can be specified explicitly with `$pat: $type`.
 ///
 /// ```
-/// # use array_fu::collect_array;
-/// let values = collect_array![(x, y) in [(1, 2), (3, 4), (5, 6)] => x + y; 3];
+/// # use array_fu::array;
+/// let values = array![x: u16 => x * x; 3];
 ///
-/// assert_eq!(values, Some([3, 7, 11]));
+/// assert_eq!(values, [0u16, 1, 4]);
 /// ```
 ///
-/// And patterns don't have to be irrefutable.
+/// Annotated type must match pattern and expression usage.
 ///
+/// ```compile_fail
+/// # use array_fu::array;
+/// let values = array![x: u8 => x; where x == 300u16; 1];
 /// ```
-/// # use array_fu::collect_array;
-/// let values = collect_array![(1, y) in [(1, 2), (3, 4), (1, 6)] => y; 2];
 ///
-/// assert_eq!(values, Some([2, 6]));
+/// Enumeration may start from any value.
+/// Add `from $start` after the pattern. Start expression is evaluated once before the first element is constructed.
+/// Enumerator type is inferred from it as well.
+///
 /// ```
-#[macro_export]
+/// # use array_fu::array;
+/// let values = array![x from 5 => x; 3];
+///
+/// assert_eq!(values, [5, 6, 7]);
+///
+/// let values = array![x from 100u8 => x.leading_zeros(); 2];
+///
+/// assert_eq!(values, [1, 1]);
+/// ```
+///
+/// Step between enumerated values is set with `by $step`.
+/// It can be combined with custom start.
+///
+/// ```
+/// # use array_fu::array;
+/// let values = array![x by 2 => x; 4];
+///
+/// assert_eq!(values, [0, 2, 4, 6]);
+///
+/// let values = array![x from 1 by 3 => x; 3];
+///
+/// assert_eq!(values, [1, 4, 7]);
+///
+/// let values = array![x by 3 => x; where x < 20; 4];
+///
+/// assert_eq!(values, [0, 3, 6, 9]);
+/// ```
+///
+/// Step must be non-zero.
+///
+/// ```should_panic
+/// # use array_fu::array;
+/// // Panics with "Enumeration step must be non-zero".
+/// let _ = array![x by 0 => x; 4];
+/// ```
+///
+/// Alternatively step can be set with `step $step` clause after element expression.
+/// Negative step makes signed enumerator count down.
+///
+/// ```
+/// # use array_fu::array;
+/// let values = array![x => x; step 3; 5];
+///
+/// assert_eq!(values, [0, 3, 6, 9, 12]);
+///
+/// let values = array![x from 2 => x; step -2; 3];
+///
+/// assert_eq!(values, [2, 0, -2]);
+/// ```
+///
+/// `rev` clause or modifier reverses enumeration order.
+/// Enumerator starts from the value that would be bound for the last element
+/// and counts backwards. Elements are still written front-to-back.
+///
+/// ```
+/// # use array_fu::array;
+/// let values = array![x => x; rev; 4];
+///
+/// assert_eq!(values, [3, 2, 1, 0]);
+///
+/// let values = array![x rev => x; 5];
+///
+/// assert_eq!(values, [4, 3, 2, 1, 0]);
+///
+/// let values = array![x from 1 by 2 => x; rev; 3];
+///
+/// assert_eq!(values, [5, 3, 1]);
+/// ```
+///
+/// `fill rev` clause constructs elements back-to-front.
+/// Element expression is evaluated for the last element first and its value is written to the last position.
+/// Enumeration is reversed as with `rev` and yields one value per element,
+/// so values rejected by predicates are not replaced.
+/// This allows suffix recurrences with [`state`](#state).
+///
+/// ```
+/// # use array_fu::{array, try_array};
+/// let values = array![x => x; fill rev; 4];
+///
+/// assert_eq!(values, [0, 1, 2, 3]);
+///
+/// let input = [1, 2, 3, 4];
+/// let suffix_sums = array![state acc = 0; x => { acc += input[x]; acc }; fill rev; 4];
+///
+/// assert_eq!(suffix_sums, [10, 9, 7, 4]);
+///
+/// let values = try_array![x => x; where x != 1; fill rev; 3];
+///
+/// assert_eq!(values, None);
+/// ```
+///
+/// Enumeration counts down when started with `down_from $start` instead.
+///
+/// ```
+/// # use array_fu::array;
+/// let values = array![x down_from 10 => x; 3];
+///
+/// assert_eq!(values, [10, 9, 8]);
+/// ```
+///
+/// ## Grid
+///
+/// `over $rows x $cols` modifier binds pattern to pairs of row and column indices in row-major order.
+/// Rows and columns are single tokens, parenthesize expressions.
+/// Enumeration ends after all pairs are tried.
+/// Without predicates, refutable patterns, multiple arms or `tries` clause
+/// array length must equal number of pairs.
+///
+/// ```
+/// # use array_fu::array;
+/// let values = array![(i, j) over 2 x 3 => i * 10 + j; 6];
+///
+/// assert_eq!(values, [0, 1, 2, 10, 11, 12]);
+///
+/// let size = 3;
+/// let values = array![(i, j) over size x (size - 1) => i * 10 + j; where i != 1; 4];
+///
+/// assert_eq!(values, [0, 1, 20, 21]);
+///
+/// let values = array![(i, 1) over 2 x 3 => i; 2];
+///
+/// assert_eq!(values, [0, 1]);
+///
+/// let values = array![(i, j) over 2 x 3 if i != j => (i, j); 4];
+///
+/// assert_eq!(values, [(0, 1), (0, 2), (1, 0), (1, 2)]);
+/// ```
+///
+/// ```should_panic
+/// # use array_fu::array;
+/// // Panics as there are only 6 pairs.
+/// let _ = array![(i, j) over 2 x 3 => i * 10 + j; 7];
+/// ```
+///
+/// ```should_panic
+/// # use array_fu::array;
+/// // Panics as 2 pairs would be left unused.
+/// let _ = array![(i, j) over 2 x 3 => i * 10 + j; 4];
+/// ```
+///
+/// ## Predicates
+///
+/// `array!` macro supports predicated that are evaluated before element expression for each constructed element.
+/// When predicate does not pass, element expression is not executed.
+/// Value bound to pattern will be updated before trying again.
+///
+/// ```
+/// # use array_fu::array;
+/// let values = array![x => x + 1; where x & 1 == 1; 3];
+///
+/// assert_eq!(values, [2, 4, 6]);
+/// ```
+///
+/// Pattern may have a guard, same as `match` arm.
+/// Guard follows enumeration modifiers.
+///
+/// ```
+/// # use array_fu::array;
+/// let values = array![x if x % 3 != 0 => x * x; 5];
+///
+/// assert_eq!(values, [1, 4, 16, 25, 49]);
+///
+/// let values = array![x from 10 by 5 if x % 10 != 0 => x; 3];
+///
+/// assert_eq!(values, [15, 25, 35]);
+/// ```
+///
+/// It is possible to make array expression infeasible.
+/// For example by providing predicate that never evaluates to true.
+///
+/// ```should_panic
+/// # use array_fu::array;
+///
+/// // predicate always evaluates to `false`
+/// // making it impossible to construct array of size 1 or greater.
+/// // This will lead to a panic with descriptive message.
+/// // `[u8; 1]` type forces enumerator to be `u8` allowing it to fail faster.
+/// let _: [u8; 1] = array![x => x; where false; 1];
+/// ```
+///
+/// Enumerating whole space of the enumerator type may take a while.
+/// `tries $budget` clause limits number of enumerator values to try.
+/// Budget expression is evaluated once before the first element is constructed.
+///
+/// ```should_panic
+/// # use array_fu::array;
+/// // Panics reporting that only 2 of 3 elements were initialized
+/// // and the predicate: `x % 1000 == 0`.
+/// let _ = array![x => x; where x % 1000 == 0; tries 1500; 3];
+/// ```
+///
+/// With [`try_array!`] running out of budget results in `None`.
+///
+/// ```
+/// # use array_fu::try_array;
+/// let values = try_array![x => x; where x % 1000 == 0; tries 1500; 3];
+///
+/// assert_eq!(values, None);
+///
+/// let values = try_array![x => x; where x % 1000 == 0; tries 2500; 3];
+///
+/// assert_eq!(values, Some([0, 1000, 2000]));
+/// ```
+///
+/// Alternatively `else $expr` clause provides elements that enumeration failed to construct.
+/// It is evaluated once for each remaining element, after enumeration is given up.
+///
+/// ```
+/// # use array_fu::array;
+/// let values = array![x => x; where x % 1000 == 0; tries 1500; else -1; 3];
+///
+/// assert_eq!(values, [0, 1000, -1]);
+/// ```
+///
+/// ## Post-conditions
+///
+/// `where after |$pat| $cond` clause checks element after it is constructed.
+/// Pattern is bound to a reference to the element.
+/// When condition does not pass, element is dropped and enumeration continues.
+/// Note that element expression is evaluated even for candidates that are rejected.
+///
+/// ```
+/// # use array_fu::array;
+/// let values = array![x => x * 7 % 10; where after |&e| e > 4; 3];
+///
+/// assert_eq!(values, [7, 8, 5]);
+/// ```
+///
+/// Infeasible post-conditions exhaust the enumerator just like predicates.
+///
+/// ```
+/// # use array_fu::try_array;
+/// let values: Option<[u8; 1]> = try_array![x => x; where after |_| false; 1];
+///
+/// assert_eq!(values, None);
+/// ```
+///
+/// `distinct` clause rejects elements equal to any of the elements constructed before.
+/// Combine it with `tries` when value space may be smaller than array length.
+///
+/// ```
+/// # use array_fu::{array, try_array};
+/// let values = array![x => x * x % 7; distinct; 4];
+///
+/// assert_eq!(values, [0, 1, 4, 2]);
+///
+/// let values = try_array![x => x % 3; distinct; tries 100; 4];
+///
+/// assert_eq!(values, None);
+/// ```
+///
+/// `sorted` clause sorts array after all elements are constructed.
+/// `sorted by $key` sorts by key function instead, same as [`slice::sort_unstable_by_key`].
+/// Sorting is not stable, elements with equal keys may be reordered.
+/// Array is not sorted when construction fails.
+///
+/// ```
+/// # use array_fu::array;
+/// let values = array![x => x * 5 % 7; where x != 0; sorted; 4];
+///
+/// assert_eq!(values, [1, 3, 5, 6]);
+///
+/// let values = array![x => (x % 3, x); sorted by |&(k, _)| k; 3];
+///
+/// assert_eq!(values, [(0, 0), (1, 1), (2, 2)]);
+/// ```
+///
+/// ## Arms
+///
+/// Multiple comma-separated `$pat => $expr` arms may be provided, like in `match` expression.
+/// For each enumerator value arms are tried in order and the first matching one constructs the element.
+/// Arms may have guards. If no arm matches, enumerator value is skipped.
+/// Predicates are checked after pattern of the arm matched,
+/// so they may only use bindings common to all arms.
+///
+/// ```
+/// # use array_fu::array;
+/// let values = array![0 => "zero", n if n % 2 == 0 => "even", _ => "odd"; 5];
+///
+/// assert_eq!(values, ["zero", "odd", "even", "odd", "even"]);
+///
+/// let values = array![1 => 10, 3 | 4 => 30; 3];
+///
+/// assert_eq!(values, [10, 30, 30]);
+/// ```
+///
+/// ## Batches
+///
+/// `yield $expr` makes element expression produce a batch of elements, anything that implements `IntoIterator`.
+/// Elements of the batch are written one by one, excess elements of the last batch are dropped.
+/// Predicates gate the whole batch, while post-conditions are checked for each element.
+///
+/// ```
+/// # use array_fu::array;
+/// let values = array![x => yield [x, x * 10]; where x > 0; 5];
+///
+/// assert_eq!(values, [1, 10, 2, 20, 3]);
+/// ```
+///
+/// `Option` is a batch of zero or one element.
+/// This makes `yield` a filter-map: `None` skips the enumerator value and `Some` is written.
+///
+/// ```
+/// # use array_fu::array;
+/// let lines = ["1", "x", "3", "", "5"];
+/// let values = array![x => yield lines[x].parse::<u32>().ok(); 3];
+///
+/// assert_eq!(values, [1, 3, 5]);
+/// ```
+///
+/// ## Bindings
+///
+/// `let $name = $init;` before enumeration pattern declares bindings
+/// evaluated once before the first element is constructed.
+/// Multiple comma-separated bindings may be declared, later ones can refer to earlier ones.
+/// They are visible in predicates and element expression and go out of scope with the macro.
+///
+/// ```
+/// # use array_fu::array;
+/// let values = array![let base = 10, scale: u32 = base / 5; x => base + x * scale; 3];
+///
+/// assert_eq!(values, [10, 12, 14]);
+/// ```
+///
+/// ## State
+///
+/// Elements may depend on a state carried between them.
+/// `state $name = $init;` before enumeration pattern declares mutable binding
+/// initialized once before the first element is constructed.
+/// It is visible in predicates and element expression.
+///
+/// ```
+/// # use array_fu::array;
+/// let values = array![state acc = 1; _ => { acc *= 2; acc }; 5];
+///
+/// assert_eq!(values, [2, 4, 8, 16, 32]);
+/// ```
+///
+/// ```
+/// # use array_fu::array;
+/// let values = array![state prev: [u64; 2] = [0, 1]; _ => {
+///     prev = [prev[1], prev[0] + prev[1]];
+///     prev[0]
+/// }; 6];
+///
+/// assert_eq!(values, [1, 1, 2, 3, 5, 8]);
+/// ```
+///
+/// ## Built prefix
+///
+/// Elements constructed so far are accessible as a slice
+/// bound to the name provided after enumeration pattern: `$pat, $name => $expr`.
+/// It can be used in predicates and element expression.
+///
+/// ```
+/// # use array_fu::array;
+/// let values = array![_, built => match built {
+///     [.., a, b] => a + b,
+///     _ => 1,
+/// }; 6];
+///
+/// assert_eq!(values, [1, 1, 2, 3, 5, 8]);
+/// ```
+///
+/// ## Inferred length
+///
+/// `_` in place of array length makes it inferred from the expected type.
+/// This works with [`try_array!`] as well.
+///
+/// ```
+/// # use array_fu::array;
+/// fn checksum(bytes: [u8; 4]) -> u32 {
+///     bytes.iter().map(|&b| b as u32).sum()
+/// }
+///
+/// assert_eq!(checksum(array![x => x; _]), 6);
+///
+/// let values: [u8; 3] = array![7; _];
+///
+/// assert_eq!(values, [7, 7, 7]);
+/// ```
+///
+/// Length must be known from the context.
+///
+/// ```compile_fail
+/// # use array_fu::array;
+/// let values = array![7; _];
+/// ```
+///
+/// ## Control flow
+///
+/// Element expressions and conditions are executed in the inner loop scope but in the outer function.
+/// This makes it possible to perform early return from macro invocation using `return` and `break` and `continue` statements.
+/// `continue` and `break` won't compile without a label. If label is provided, they will behave as expected.
+/// `return` would exit function where macro is called.
+/// If size of the array is `0`, element and condition expressions won't be executed even once
+/// and `return` statement won't exit the function.
+/// This behavior is different from `[return; 0]` which performs early return regardless.
+///
+/// ```
+/// # use array_fu::array;
+/// array![return; 1];
+/// ```
+///
+/// ```compile_fail
+/// # use array_fu::array;
+/// array![break; 1];
+/// ```
+///
+/// ```compile_fail
+/// # use array_fu::array;
+/// array![continue; 1];
+/// ```
+///
+/// ```
+/// # use array_fu::array;
+/// 'a: loop { array![break 'a; 1]; };
+/// ```
+///
+/// ```
+/// # use array_fu::array;
+/// 'a: for _ in 0..3 { array![continue 'a; 1]; };
+/// ```
+///
+/// `retry $label` clause labels enumeration loop.
+/// `continue $label` discards current attempt from anywhere in the element expression
+/// and tries the next enumerator value, just like failed predicate does.
+///
+/// ```
+/// # use array_fu::array;
+/// let words = ["one", "", "three", "four"];
+/// let values = array![x => match words[x].chars().next() {
+///     Some(c) => c,
+///     None => continue 'skip,
+/// }; retry 'skip; 3];
+///
+/// assert_eq!(values, ['o', 't', 'f']);
+/// ```
+///
+/// `break` with the label won't compile, as array would be left incomplete.
+///
+/// ```compile_fail
+/// # use array_fu::array;
+/// let _ = array![x => if x > 1 { break 'skip } else { x }; retry 'skip; 3];
+/// ```
+///
+/// ## Generator
+///
+/// `while let $pat = $source => $expr` form re-evaluates source expression for each element,
+/// like `while let` loop does.
+/// Unlike other forms it returns `Option`, `None` is returned if pattern fails to match
+/// before array is filled.
+///
+/// ```
+/// # use array_fu::array;
+/// let mut tokens = "let x = 42".split(' ');
+/// let values = array![while let Some(token) = tokens.next() => token.len(); where token != "="; 3];
+///
+/// assert_eq!(values, Some([3, 1, 2]));
+///
+/// let values = array![while let Some(token) = tokens.next() => token.len(); 1];
+///
+/// assert_eq!(values, None);
+/// ```
+///
+/// ## Clone
+///
+/// `clone $value` evaluates value once and fills array with its clones.
+/// Original value is moved into the last element, saving one clone compared to `$value.clone()`.
+///
+/// ```
+/// # use array_fu::array;
+/// let values = array![clone vec![1, 2, 3]; 3];
+///
+/// assert_eq!(values, [[1, 2, 3], [1, 2, 3], [1, 2, 3]]);
+/// ```
+///
+/// ## List
+///
+/// For consistency with built-in syntax, arrays may be constructed with a list of expressions.
+///
+/// ```
+/// # use array_fu::array;
+/// let values = array![1, 2, 3];
+///
+/// assert_eq!(values, [1, 2, 3]);
+/// ```
+///
+/// Listed elements may be followed by `.., $fill` item and array length.
+/// Fill expression is evaluated for each remaining element, same as in repeat form.
+///
+/// ```
+/// # use array_fu::array;
+/// let header = array![0x7f, b'E', .., 0u8; 6];
+///
+/// assert_eq!(header, [0x7f, b'E', 0, 0, 0, 0]);
+///
+/// let names = array![String::from("root"), .., String::new(); 3];
+///
+/// assert_eq!(names, ["root", "", ""]);
+/// ```
+///
+/// Listing more elements than array length won't compile.
+///
+/// ```compile_fail
+/// # use array_fu::array;
+/// let _ = array![1, 2, 3, .., 0; 2];
+/// ```
+///
+/// `..$array` item spreads elements of another array, moving them into the new one.
+/// Spread array longer than the whole array fails to compile.
+/// Spread arrays that don't fit after preceding items cause a panic.
+///
+/// ```
+/// # use array_fu::array;
+/// let small = [String::from("a"), String::from("b")];
+/// let values = array![..small, String::from("c"), ..[String::from("d")], String::new(); 5];
+///
+/// assert_eq!(values, ["a", "b", "c", "d", ""]);
+/// ```
+///
+/// ```compile_fail
+/// # use array_fu::array;
+/// let _ = array![..[1, 2, 3], 0; 2];
+/// ```
+///
+/// ```should_panic
+/// # use array_fu::array;
+/// let _ = array![1, ..[2, 3], 0; 2];
+/// ```
+#[macro_export]
+macro_rules! array {
+    (while let $p:pat = $src:expr => $e:expr $(; where $($cond:expr),+ )? ; $n:expr) => {
+        $crate::array_impl!(@while [$p] [$src] [$e] [$($($cond,)+)?] $n)
+    };
+
+    (clone $v:expr; _) => {
+        $crate::array_impl!(@clone [] $v)
+    };
+
+    (clone $v:expr; $n:expr) => {
+        $crate::array_impl!(@clone [$n] $v)
+    };
+
+    ($($e:expr),* $(,)?) => { [$($e,)*] };
+
+    ($e:expr; _) => {{
+        $crate::array!( _ => $e ; _ )
+    }};
+
+    ($e:expr; $n:expr) => {{
+        $crate::array!( _ => $e ; $n )
+    }};
+
+    ($($t:tt)+) => {
+        $crate::array_impl!(@pre panic $($t)+)
+    };
+}
+
+/// Constructs boxed array directly on the heap.
+///
+/// Syntax is the same as for [`array!`] with repeated expression or enumeration.
+/// Storage is allocated before elements are constructed and each element is written in place,
+/// so arrays too large for the stack can be built.
+///
+/// Requires `alloc` feature.
+///
+/// ```
+/// # use array_fu::boxed_array;
+/// let values: Box<[u32; 4]> = boxed_array![x => x * 2; where x % 3 != 0; 4];
+///
+/// assert_eq!(*values, [2, 4, 8, 10]);
+///
+/// let zeros = boxed_array![0u8; 1 << 24];
+///
+/// assert!(zeros.iter().all(|&x| x == 0));
+/// ```
+///
+/// If element expression panics, elements constructed so far are dropped
+/// and allocation is freed.
+#[cfg(feature = "alloc")]
+#[macro_export]
+macro_rules! boxed_array {
+    ($e:expr; _) => {{
+        $crate::boxed_array!( _ => $e ; _ )
+    }};
+
+    ($e:expr; $n:expr) => {{
+        $crate::boxed_array!( _ => $e ; $n )
+    }};
+
+    ($($t:tt)+) => {
+        $crate::array_impl!(@pre boxed $($t)+)
+    };
+}
+
+/// Constructs `Vec` of length chosen at runtime.
+///
+/// Syntax is the same as for [`array!`], except that length is any `usize` expression.
+/// Capacity for all elements is reserved before elements are constructed.
+///
+/// Requires `alloc` feature.
+///
+/// ```
+/// # use array_fu::vec_fu;
+/// let n = "four".len();
+/// let values = vec_fu![x => x * 2; where x % 3 != 0; n];
+///
+/// assert_eq!(values, [2, 4, 8, 10]);
+///
+/// let zeros = vec_fu![0u8; n + 1];
+///
+/// assert_eq!(zeros, [0; 5]);
+///
+/// let words = vec_fu!["a", "b", "c"];
+///
+/// assert_eq!(words, ["a", "b", "c"]);
+/// ```
+#[cfg(feature = "alloc")]
+#[macro_export]
+macro_rules! vec_fu {
+    () => { $crate::__Vec::new() };
+
+    ($($e:expr),+ $(,)?) => {{
+        let mut vec = $crate::__Vec::with_capacity([$(stringify!($e)),+].len());
+        $( vec.push($e); )+
+        vec
+    }};
+
+    ($e:expr; $n:expr) => {{
+        $crate::vec_fu!( _ => $e ; $n )
+    }};
+
+    ($($t:tt)+) => {
+        $crate::array_impl!(@pre vec $($t)+)
+    };
+}
+
+/// Constructs two-dimensional array `[[T; COLS]; ROWS]`.
+///
+/// Pattern is bound to pairs of row and column indices.
+/// Elements are constructed in row-major order.
+///
+/// ```
+/// # use array_fu::array2d;
+/// let identity = array2d![(r, c) => (r == c) as u8; 3, 3];
+///
+/// assert_eq!(identity, [[1, 0, 0], [0, 1, 0], [0, 0, 1]]);
+///
+/// let values = array2d![(r, c) => r * 10 + c; 2, 3];
+///
+/// assert_eq!(values, [[0, 1, 2], [10, 11, 12]]);
+/// ```
+///
+/// Predicates reject cells, rejected cells are filled with `else` expression.
+/// Cells keep their positions, so `else` is required with `where`.
+///
+/// ```
+/// # use array_fu::array2d;
+/// let upper = array2d![(r, c) => r * 10 + c; where r <= c; else 0; 2, 3];
+///
+/// assert_eq!(upper, [[0, 1, 2], [0, 11, 12]]);
+/// ```
+///
+/// ```compile_fail
+/// # use array_fu::array2d;
+/// let upper = array2d![(r, c) => r * 10 + c; where r <= c; 2, 3];
+/// ```
+///
+/// Repeated expression and list forms are supported too.
+///
+/// ```
+/// # use array_fu::array2d;
+/// assert_eq!(array2d![0; 2, 2], [[0, 0], [0, 0]]);
+/// assert_eq!(array2d![[1, 2], [3, 4]], [[1, 2], [3, 4]]);
+/// ```
+#[macro_export]
+macro_rules! array2d {
+    ($e:expr; $rows:expr, $cols:expr) => {
+        $crate::array2d!(_ => $e; $rows, $cols)
+    };
+
+    ($p:pat => $e:expr ; where $($cond:expr),+ ; else $f:expr ; $rows:expr, $cols:expr) => {
+        $crate::array![row: usize => $crate::array![
+            col: usize => {
+                #[allow(unused_variables)]
+                let $p = (row, col);
+                if true $(&& $cond)+ { $e } else { $f }
+            };
+            $cols
+        ]; $rows]
+    };
+
+    ($p:pat => $e:expr ; where $($cond:expr),+ ; $rows:expr, $cols:expr) => {
+        compile_error!("`where` in `array2d!` requires `else` expression for rejected cells")
+    };
+
+    ($p:pat => $e:expr ; $rows:expr, $cols:expr) => {
+        $crate::array![row: usize => $crate::array![
+            col: usize => {
+                #[allow(unused_variables)]
+                let $p = (row, col);
+                $e
+            };
+            $cols
+        ]; $rows]
+    };
+
+    ($([$($e:expr),* $(,)?]),* $(,)?) => {
+        [$([$($e,)*],)*]
+    };
+}
+
+/// Constructs array of futures with [`array!`] syntax
+/// and returns future that resolves to array of their outputs.
+///
+/// Futures are polled concurrently, outputs preserve index order.
+/// See [`join::JoinArray`].
+///
+/// Requires `futures` feature.
+///
+/// ```
+/// # use array_fu::join_array;
+/// # async fn fetch(shard: usize) -> usize { shard * 100 }
+/// # async fn example() {
+/// let shards = join_array![i => fetch(i); 4].await;
+///
+/// assert_eq!(shards, [0, 100, 200, 300]);
+/// # }
+/// ```
+#[cfg(feature = "futures")]
+#[macro_export]
+macro_rules! join_array {
+    ($($t:tt)+) => {
+        $crate::join::JoinArray::new($crate::array![$($t)+])
+    };
+}
+
+/// Constructs array of running accumulator values, like [`Iterator::scan`].
+///
+/// Accumulator is initialized with the second argument.
+/// For each element of the array, closure-like expression is executed
+/// with mutable reference to the accumulator and moved element.
+/// As with [`array_map!`], closure-like expression is executed in the outer function.
+///
+/// ```
+/// # use array_fu::array_scan;
+/// let sums = array_scan!([1, 2, 3, 4], 0, |acc, x| {
+///     *acc += x;
+///     *acc
+/// });
+///
+/// assert_eq!(sums, [1, 3, 6, 10]);
+/// ```
+///
+/// Any `FnMut(&mut S, T) -> U` can be used too.
+///
+/// ```
+/// # use array_fu::array_scan;
+/// fn push(acc: &mut String, word: &str) -> usize {
+///     acc.push_str(word);
+///     acc.len()
+/// }
+///
+/// assert_eq!(array_scan!(["a", "bc", "d"], String::new(), push), [1, 3, 4]);
+/// ```
+///
+/// If expression panics, accumulator and elements constructed so far are dropped.
+#[macro_export]
+macro_rules! array_scan {
+    ($a:expr, $init:expr, |$acc:pat_param, $x:pat_param| $e:expr $(,)?) => {{
+        let mut state = $init;
+        $crate::array_map!($a, item => {
+            let $acc = &mut state;
+            let $x = item;
+            $e
+        })
+    }};
+
+    ($a:expr, $init:expr, $f:expr $(,)?) => {{
+        let mut state = $init;
+        let mut f = $f;
+        $crate::array_map!($a, item => f(&mut state, item))
+    }};
+}
+
+/// Constructs array of `N` elements by calling closure with index of each element.
+///
+/// Closure-like `|$pat| $expr` form and any `FnMut(usize) -> T` are accepted.
+/// As with [`array_map!`], closure-like expression is executed in the outer function.
+///
+/// ```
+/// # use array_fu::array_init_with;
+/// let squares = array_init_with!(4, |i| i * i);
+///
+/// assert_eq!(squares, [0, 1, 4, 9]);
+///
+/// let names: [String; 2] = array_init_with!(_, |i| format!("item{}", i));
+///
+/// assert_eq!(names, ["item0", "item1"]);
+///
+/// let offset = |i: usize| i + 10;
+///
+/// assert_eq!(array_init_with!(2, offset), [10, 11]);
+/// ```
+///
+/// If closure panics, elements constructed so far are dropped.
+#[macro_export]
+macro_rules! array_init_with {
+    (_, |$i:pat_param| $e:expr $(,)?) => {
+        $crate::array![index: usize => {
+            let $i = index;
+            $e
+        }; _]
+    };
+
+    (_, $f:expr $(,)?) => {{
+        let mut f = $f;
+        $crate::array![index: usize => f(index); _]
+    }};
+
+    ($n:expr, |$i:pat_param| $e:expr $(,)?) => {
+        $crate::array![index: usize => {
+            let $i = index;
+            $e
+        }; $n]
+    };
+
+    ($n:expr, $f:expr $(,)?) => {{
+        let mut f = $f;
+        $crate::array![index: usize => f(index); $n]
+    }};
+}
+
+/// Constructs array with an accumulator carried between elements.
+///
+/// `scan_array![$acc = $init; ...]` is the same as `array![state $acc = $init; ...]`.
+/// Accumulator is declared once and is visible in predicates and element expression.
+///
+/// ```
+/// # use array_fu::scan_array;
+/// let input = [3, 1, 4, 1];
+/// let sums = scan_array![acc = 0; x => { acc += input[x]; acc }; 4];
+///
+/// assert_eq!(sums, [3, 4, 8, 9]);
+///
+/// let odd = scan_array![acc: u64 = 1; x => { acc *= x; acc }; where x % 2 == 1; 3];
+///
+/// assert_eq!(odd, [1, 3, 15]);
+/// ```
+#[macro_export]
+macro_rules! scan_array {
+    ($acc:ident $(: $t:ty)? = $init:expr ; $($rest:tt)+) => {
+        $crate::array!(state $acc $(: $t)? = $init ; $($rest)+)
+    };
+}
+
+/// Same as [`array_map!`].
+///
+/// ```
+/// # use array_fu::map_array;
+/// let values = map_array![[1, 2, 3], x => x * 2];
+///
+/// assert_eq!(values, [2, 4, 6]);
+/// ```
+#[macro_export]
+macro_rules! map_array {
+    ($($t:tt)+) => {
+        $crate::array_map!($($t)+)
+    };
+}
+
+/// Constructs array like [`array!`] does, evaluating element expressions in parallel
+/// on rayon thread pool.
+///
+/// Pattern is bound to index of the element.
+/// Predicates and other clauses are not supported.
+/// Unlike with [`array!`], expression is executed in a closure,
+/// so it can't return from the outer function.
+///
+/// Requires `rayon` feature.
+///
+/// ```
+/// # use array_fu::par_array;
+/// let values = par_array![x => x * x; 4];
+///
+/// assert_eq!(values, [0, 1, 4, 9]);
+/// ```
+///
+/// If any element expression panics, elements constructed so far are dropped.
+#[cfg(feature = "rayon")]
+#[macro_export]
+macro_rules! par_array {
+    ($e:expr; _) => {
+        $crate::par_array!(_ => $e; _)
+    };
+
+    ($e:expr; $n:expr) => {
+        $crate::par_array!(_ => $e; $n)
+    };
+
+    ($p:pat => $e:expr; _) => {
+        $crate::par::par_array(|index: usize| {
+            let $p = index;
+            $e
+        })
+    };
+
+    ($p:pat => $e:expr; $n:expr) => {
+        $crate::par::par_array::<_, _, { $n }>(|index: usize| {
+            let $p = index;
+            $e
+        })
+    };
+}
+
+/// Constructs array by mapping elements of another array.
+///
+/// Elements are moved into the pattern one by one, front-to-back.
+/// Closure-like `|$pat| $expr` and `$pat => $expr` forms are supported.
+/// As with [`array!`], expression is executed in the outer function.
+///
+/// ```
+/// # use array_fu::array_map;
+/// let values = array_map!([1, 2, 3], |x| x * 2);
+///
+/// assert_eq!(values, [2, 4, 6]);
+///
+/// let names = array_map!(values, x => x.to_string());
+///
+/// assert_eq!(names, ["2", "4", "6"]);
+/// ```
+///
+/// If expression panics, elements mapped so far and elements not yet mapped are dropped.
+#[macro_export]
+macro_rules! array_map {
+    ($a:expr, |$p:pat_param| $e:expr $(,)?) => {
+        $crate::array_map!($a, $p => $e)
+    };
+
+    ($a:expr, $p:pat => $e:expr $(,)?) => {{
+        let source = $a;
+        let mut array = $crate::map_builder(&source);
+
+        for item in source {
+            let $p = item;
+
+            #[allow(unreachable_code)]
+            {
+                #[allow(unused_variables)]
+                let elem = $crate::array_impl!(@body [] $e);
+
+                unsafe {
+                    // SAFETY: Source array has the same length.
+                    array.write(elem);
+                }
+            }
+        }
+
+        unsafe {
+            // SAFETY: Every element of the source array was mapped.
+            array.assume_init()
+        }
+    }};
+}
+
+/// Zips arrays of the same length into array of tuples.
+///
+/// Elements are moved out of arrays.
+/// References to arrays produce tuples of references.
+///
+/// ```
+/// # use array_fu::array_zip;
+/// let names = [String::from("a"), String::from("b")];
+/// let values = [1, 2];
+///
+/// assert_eq!(array_zip!(&names, values), [(&names[0], 1), (&names[1], 2)]);
+/// assert_eq!(
+///     array_zip!(names, values, [true, false]),
+///     [(String::from("a"), 1, true), (String::from("b"), 2, false)]
+/// );
+/// ```
+///
+/// Arrays must have the same length.
+///
+/// ```compile_fail
+/// # use array_fu::array_zip;
+/// let _ = array_zip!([1, 2], [1, 2, 3]);
+/// ```
+#[macro_export]
+macro_rules! array_zip {
+    ($($a:expr),+ $(,)?) => {
+        $crate::array_zip!(@name [] [a b c d e f g h i j k l] $($a,)+)
+    };
+
+    // Gives unique name to each array.
+    (@name [$($done:tt)*] [$n:ident $($names:ident)*] $a:expr, $($rest:tt)*) => {
+        $crate::array_zip!(@name [$($done)* ($n $a)] [$($names)*] $($rest)*)
+    };
+
+    (@name [$(($n:ident $a:expr))+] [$($names:ident)*]) => {{
+        #[allow(unused_mut)]
+        let mut array = $crate::builder::PartiallyInitArray::uninit();
+        $( let mut $n = $crate::ArrayIter::into_array_iter($a, &array); )+
+
+        loop {
+            match ($( $crate::Iterator::next(&mut $n), )+) {
+                ($( Some($n), )+) => unsafe {
+                    // SAFETY: Arrays have the same length as the output.
+                    array.write(($( $n, )+))
+                },
+                _ => break,
+            }
+        }
+
+        unsafe {
+            // SAFETY: All arrays were iterated to the end.
+            array.assume_init()
+        }
+    }};
+}
+
+/// Combines elements of arrays of the same length with an expression.
+///
+/// Array variables are shadowed by their elements in the expression.
+/// Alternatively `$pat in $array` form binds elements to patterns.
+/// Elements are moved, same as with [`array_zip!`].
+///
+/// ```
+/// # use array_fu::zip_arrays;
+/// let a = [1, 2, 3];
+/// let b = [10, 20, 30];
+///
+/// assert_eq!(zip_arrays![a, b => a + b], [11, 22, 33]);
+/// assert_eq!(zip_arrays![x in a, y in &b, z in [1, 0, 1] => x * y * z], [10, 0, 90]);
+/// ```
+#[macro_export]
+macro_rules! zip_arrays {
+    ($($p:pat in $a:expr),+ => $e:expr $(,)?) => {
+        $crate::array_map!($crate::array_zip!($($a),+), ($($p,)+) => $e)
+    };
+
+    ($($a:ident),+ => $e:expr $(,)?) => {
+        $crate::array_map!($crate::array_zip!($($a),+), ($($a,)+) => $e)
+    };
+}
+
+/// Concatenates arrays into one array.
+///
+/// Length of the resulting array is specified after `;`
+/// or inferred from the expected type with `_`.
+/// Elements are moved, references to arrays produce arrays of references.
+///
+/// ```
+/// # use array_fu::array_concat;
+/// let values = array_concat!([1, 2], [3, 4, 5]; 5);
+///
+/// assert_eq!(values, [1, 2, 3, 4, 5]);
+///
+/// let values: [u32; 4] = array_concat!([1], [2], [3, 4]; _);
+///
+/// assert_eq!(values, [1, 2, 3, 4]);
+/// ```
+///
+/// Panics if lengths of the arrays do not add up to the specified length.
+///
+/// ```should_panic
+/// # use array_fu::array_concat;
+/// let _ = array_concat!([1, 2], [3, 4, 5]; 4);
+/// ```
+#[macro_export]
+macro_rules! array_concat {
+    ($($a:expr),+ $(,)? ; _) => {{
+        let mut array = $crate::builder::PartiallyInitArray::uninit();
+        $( $crate::concat_extend(&mut array, $a); )+
+        $crate::concat_finish(array)
+    }};
+
+    ($($a:expr),+ $(,)? ; $n:expr) => {{
+        let mut array = $crate::builder::PartiallyInitArray::<_, { $n }>::uninit();
+        $( $crate::concat_extend(&mut array, $a); )+
+        $crate::concat_finish(array)
+    }};
+}
+
+/// Splits array into two arrays.
+///
+/// Lengths of the resulting arrays are specified after `;`
+/// or inferred from the expected type with `_, _`.
+/// Elements are moved, the first ones go into the left array.
+///
+/// ```
+/// # use array_fu::array_split;
+/// let (header, body) = array_split!([1, 2, 3, 4, 5]; 2, 3);
+///
+/// assert_eq!(header, [1, 2]);
+/// assert_eq!(body, [3, 4, 5]);
+///
+/// let (header, body): ([u8; 1], [u8; 3]) = array_split!([1, 2, 3, 4]; _, _);
+///
+/// assert_eq!(header, [1]);
+/// assert_eq!(body, [2, 3, 4]);
+/// ```
+///
+/// Lengths must add up to the length of the source array.
+///
+/// ```compile_fail
+/// # use array_fu::array_split;
+/// let _ = array_split!([1, 2, 3, 4, 5, 6, 7, 8]; 3, 4);
+/// ```
+#[macro_export]
+macro_rules! array_split {
+    ($a:expr; _, _) => {
+        $crate::split_array($a)
+    };
+
+    ($a:expr; $m:expr, $k:expr) => {{
+        let (left, right): ([_; $m], [_; $k]) = $crate::split_array($a);
+        (left, right)
+    }};
+}
+
+/// Flattens array of arrays into one array in row-major order.
+///
+/// Length of the resulting array is specified after `;`
+/// or inferred from the expected type with `_`.
+/// Elements are moved, neither `Clone` nor `Copy` is required.
+///
+/// ```
+/// # use array_fu::array_flatten;
+/// let addresses = [[127, 0, 0, 1], [10, 0, 0, 1]];
+/// let bytes = array_flatten!(addresses; 8);
+///
+/// assert_eq!(bytes, [127, 0, 0, 1, 10, 0, 0, 1]);
+///
+/// let bytes: [u8; 4] = array_flatten!([[1, 2], [3, 4]]; _);
+///
+/// assert_eq!(bytes, [1, 2, 3, 4]);
+/// ```
+///
+/// Length must match total number of elements.
+///
+/// ```compile_fail
+/// # use array_fu::array_flatten;
+/// let _ = array_flatten!([[1, 2], [3, 4]]; 3);
+/// ```
+#[macro_export]
+macro_rules! array_flatten {
+    ($a:expr; _) => {
+        $crate::flatten_array($a)
+    };
+
+    ($a:expr; $k:expr) => {{
+        let array: [_; $k] = $crate::flatten_array($a);
+        array
+    }};
+}
+
+/// Pairs each element of array with its index.
+///
+/// Indices start from `0` or from value provided with `start: $index`.
+///
+/// ```
+/// # use array_fu::array_enumerate;
+/// let values = array_enumerate!(["a", "b", "c"]);
+///
+/// assert_eq!(values, [(0, "a"), (1, "b"), (2, "c")]);
+///
+/// let values = array_enumerate!(["a", "b", "c"], start: 5);
+///
+/// assert_eq!(values, [(5, "a"), (6, "b"), (7, "c")]);
+/// ```
+#[macro_export]
+macro_rules! array_enumerate {
+    ($a:expr $(,)?) => {
+        $crate::array_enumerate!($a, start: 0)
+    };
+
+    ($a:expr, start: $s:expr $(,)?) => {{
+        let mut index: $crate::Usize = $s;
+        $crate::array_map!($a, elem => {
+            let i = index;
+            index = index.wrapping_add(1);
+            (i, elem)
+        })
+    }};
+}
+
+/// Splits array of pairs into pair of arrays.
+///
+/// Inverse of [`array_zip!`] for two arrays.
+/// Elements are moved, neither `Clone` nor `Copy` is required.
+///
+/// ```
+/// # use array_fu::array_unzip;
+/// let pairs = [(1, String::from("a")), (2, String::from("b"))];
+/// let (numbers, names) = array_unzip!(pairs);
+///
+/// assert_eq!(numbers, [1, 2]);
+/// assert_eq!(names, ["a", "b"]);
+/// ```
+#[macro_export]
+macro_rules! array_unzip {
+    ($a:expr $(,)?) => {{
+        let array = $a;
+        let (mut left, mut right) = $crate::unzip_builders(&array);
+
+        for (l, r) in array {
+            unsafe {
+                // SAFETY: Source array has the same length.
+                left.write(l);
+                right.write(r);
+            }
+        }
+
+        unsafe {
+            // SAFETY: Whole source array was consumed.
+            (left.assume_init(), right.assume_init())
+        }
+    }};
+}
+
+/// Constructs array of uninitialized elements, same as [`uninit_array`](fn@uninit_array) function.
+///
+/// ```
+/// # use array_fu::uninit_array;
+/// let mut array = uninit_array![String; 2];
+/// array[0].write("a".into());
+/// array[1].write("b".into());
+///
+/// let array = unsafe { array_fu::assume_init_array(array) };
+/// assert_eq!(array, ["a", "b"]);
+/// ```
+#[macro_export]
+macro_rules! uninit_array {
+    ($t:ty; $n:expr) => {
+        $crate::uninit_array::<$t, { $n }>()
+    };
+}
+
+/// Constructs arrays in const context, such as `const` and `static` initializers.
+///
+/// Supports subset of [`array!`] syntax: element expression with optional enumeration pattern and predicates.
+/// Enumerator type is always `usize`.
+/// Element expression and predicates must be const-evaluable.
+///
+/// ```
+/// # use array_fu::const_array;
+/// const fn crc_step(byte: u16) -> u16 {
+///     let mut crc = byte << 8;
+///     let mut bit = 0;
+///     while bit < 8 {
+///         crc = if crc & 0x8000 != 0 { (crc << 1) ^ 0x1021 } else { crc << 1 };
+///         bit += 1;
+///     }
+///     crc
+/// }
+///
+/// static TABLE: [u16; 256] = const_array![i => crc_step(i as u16); 256];
+///
+/// assert_eq!(TABLE[1], 0x1021);
+///
+/// const ODD: [usize; 3] = const_array![x => x; where x % 2 == 1; 3];
+///
+/// assert_eq!(ODD, [1, 3, 5]);
+/// ```
+///
+/// ```compile_fail
+/// # use array_fu::const_array;
+/// static VALUES: [u32; 2] = const_array![std::process::id(); 2];
+/// ```
+#[macro_export]
+macro_rules! const_array {
+    ($e:expr; $n:expr) => {
+        $crate::const_array!(_ => $e ; $n)
+    };
+
+    ($p:pat => $e:expr $(; where $($cond:expr),+ )? ; $n:expr) => {{
+        let mut array = $crate::uninit_array::<_, { $n }>();
+        let len: $crate::Usize = $n;
+        let mut init: $crate::Usize = 0;
+        let mut next: $crate::Usize = 0;
+
+        while init < len {
+            let value = next;
+            next = next.wrapping_add(1);
+
+            #[allow(unreachable_patterns)]
+            match value {
+                $p => {
+                    if true $($( && $cond )+)? {
+                        array[init] = $crate::MaybeUninit::new($e);
+                        init += 1;
+                    }
+                }
+                _ => {}
+            }
+
+            if next == 0 && init < len {
+                panic!("Failed to initialize array using whole 'usize' space");
+            }
+        }
+
+        unsafe {
+            // SAFETY: Loop above initialized all elements.
+            $crate::assume_init_array(array)
+        }
+    }};
+}
+
+/// Constructs arrays like [`array!`] does, but returns `None` instead of panicking
+/// when array cannot be filled.
+///
+/// Syntax is identical to [`array!`] with enumeration.
+/// `Some` array is returned when enough elements were constructed.
+///
+/// ```
+/// # use array_fu::try_array;
+/// let values = try_array![x => x + 1; where x & 1 == 1; 3];
+///
+/// assert_eq!(values, Some([2, 4, 6]));
+/// ```
+///
+/// When enumerator wraps around before array is filled, `None` is returned.
+///
+/// ```
+/// # use array_fu::try_array;
+/// // `[u8; 1]` type forces enumerator to be `u8`.
+/// let values: Option<[u8; 1]> = try_array![x => x; where false; 1];
+///
+/// assert_eq!(values, None);
+/// ```
+///
+/// This is the `Option` counterpart of [`array_result!`],
+/// which stops construction at the first `Err` returned by element expression.
+#[macro_export]
+macro_rules! try_array {
+    (while let $p:pat = $src:expr => $e:expr $(; where $($cond:expr),+ )? ; $n:expr) => {
+        $crate::array_impl!(@while [$p] [$src] [$e] [$($($cond,)+)?] $n)
+    };
+
+    ($e:expr; _) => {{
+        $crate::try_array!( _ => $e ; _ )
+    }};
+
+    ($e:expr; $n:expr) => {{
+        $crate::try_array!( _ => $e ; $n )
+    }};
+
+    ($($t:tt)+) => {
+        $crate::array_impl!(@pre option $($t)+)
+    };
+}
+
+/// Constructs arrays like [`array!`] does from element expression that returns `Result`.
+///
+/// `Ok` values become elements of the array.
+/// First `Err` stops construction and is returned,
+/// elements constructed so far are dropped.
+///
+/// ```
+/// # use array_fu::array_result;
+/// let values = array_result![x => "1 2 3".split(' ').nth(x).unwrap().parse::<u32>(); 3];
+///
+/// assert_eq!(values, Ok([1, 2, 3]));
+///
+/// let values = array_result![x => "1 x 3".split(' ').nth(x).unwrap().parse::<u32>(); 3];
+///
+/// assert!(values.is_err());
+/// ```
+///
+/// `where try $pred` clause accepts predicates that return `Result<bool, E>`.
+/// `Err` stops construction the same way.
+///
+/// ```
+/// # use array_fu::array_result;
+/// let words = ["1", "-2", "3", "4"];
+/// let positive = |x: usize| words[x].parse::<i32>().map(|v| v > 0);
+///
+/// let values = array_result![x => Ok(words[x]); where try positive(x); 3];
+/// assert_eq!(values, Ok::<_, std::num::ParseIntError>(["1", "3", "4"]));
+///
+/// let words = ["1", "x", "3", "4"];
+/// let positive = |x: usize| words[x].parse::<i32>().map(|v| v > 0);
+///
+/// let values = array_result![x => Ok(words[x]); where try positive(x); 3];
+/// assert!(values.is_err());
+/// ```
+///
+/// Note that `?` operator in element expression of any macro in this crate
+/// returns from the enclosing function, dropping elements constructed so far.
+///
+/// ```
+/// # use array_fu::array;
+/// fn parse(s: &str) -> Result<[u32; 3], std::num::ParseIntError> {
+///     let mut words = s.split(' ');
+///     Ok(array![words.next().unwrap_or_default().parse()?; 3])
+/// }
+///
+/// assert_eq!(parse("1 2 3"), Ok([1, 2, 3]));
+/// assert!(parse("1 x 3").is_err());
+/// ```
+#[macro_export]
+macro_rules! array_result {
+    ($e:expr; _) => {{
+        $crate::array_result!( _ => $e ; _ )
+    }};
+
+    ($e:expr; $n:expr) => {{
+        $crate::array_result!( _ => $e ; $n )
+    }};
+
+    ($($t:tt)+) => {
+        'array_result: {
+            $crate::array_impl!(@pre (result 'array_result) $($t)+)
+        }
+    };
+}
+
+#[doc(hidden)]
+#[macro_export]
+macro_rules! array_impl {
+    // Declares state bindings before enumeration.
+    (@pre $mode:tt state $s:ident $(: $t:ty)? = $init:expr ; $($rest:tt)+) => {{
+        #[allow(unused_mut)]
+        let mut $s $(: $t)? = $init;
+        $crate::array_impl!(@pre $mode $($rest)+)
+    }};
+
+    // Declares immutable bindings before enumeration.
+    (@pre $mode:tt let $($b:ident $(: $t:ty)? = $init:expr),+ ; $($rest:tt)+) => {{
+        $( let $b $(: $t)? = $init; )+
+        $crate::array_impl!(@pre $mode $($rest)+)
+    }};
+
+    (@pre $mode:tt $($rest:tt)+) => {
+        $crate::array_impl!(@scan $mode [] $($rest)+)
+    };
+
+    // Looks for list with spreads or fill expression before the pattern ends.
+    (@scan $mode:tt [] .. $($rest:tt)+) => {
+        $crate::array_impl!(@list $mode [] [] .. $($rest)+)
+    };
+
+    (@scan $mode:tt [$($l:tt)*] , .. $($rest:tt)+) => {
+        $crate::array_impl!(@list $mode [] [] $($l)* , .. $($rest)+)
+    };
+
+    (@scan $mode:tt [$($l:tt)*] => $($rest:tt)*) => {
+        $crate::array_impl!(@pat $mode [] $($l)* => $($rest)*)
+    };
+
+    (@scan $mode:tt [$($l:tt)*] $t:tt $($rest:tt)*) => {
+        $crate::array_impl!(@scan $mode [$($l)* $t] $($rest)*)
+    };
+
+    (@scan $mode:tt [$($l:tt)*]) => {
+        $crate::array_impl!(@pat $mode [] $($l)*)
+    };
+
+    // Collects enumeration pattern tokens up to `=>` or first modifier.
+    // Modifier keywords are only recognized after pattern tokens, so they may be used as names.
+    (@pat $mode:tt [$($p:tt)*] => $($rest:tt)*) => {
+        $crate::array_impl!(@elem $mode [$($p)*] [] [] $($rest)*)
+    };
+
+    (@pat $mode:tt [$($p:tt)*] , $b:ident => $($rest:tt)*) => {
+        $crate::array_impl!(@elem $mode [$($p)*] [] [$b] $($rest)*)
+    };
+
+    (@pat $mode:tt [$($p:tt)+] : $($rest:tt)*) => {
+        $crate::array_impl!(@mod $mode [$($p)*] [] of_type [] $($rest)*)
+    };
+
+    (@pat $mode:tt [$($p:tt)+] over $r:tt x $c:tt $($rest:tt)*) => {
+        $crate::array_impl!(@mod $mode [$($p)*] [] over [$r, $c] $($rest)*)
+    };
+
+    (@pat $mode:tt [$($p:tt)+] from $($rest:tt)*) => {
+        $crate::array_impl!(@mod $mode [$($p)*] [] from [] $($rest)*)
+    };
+
+    (@pat $mode:tt [$($p:tt)+] rev $($rest:tt)*) => {
+        $crate::array_impl!(@mod $mode [$($p)*] [] reversed [] $($rest)*)
+    };
+
+    (@pat $mode:tt [$($p:tt)+] by $($rest:tt)*) => {
+        $crate::array_impl!(@mod $mode [$($p)*] [] by [] $($rest)*)
+    };
+
+    (@pat $mode:tt [$($p:tt)+] down_from $($rest:tt)*) => {
+        $crate::array_impl!(@mod $mode [$($p)*] [] down_from [] $($rest)*)
+    };
+
+    (@pat $mode:tt [$($p:tt)*] $t:tt $($rest:tt)*) => {
+        $crate::array_impl!(@pat $mode [$($p)* $t] $($rest)*)
+    };
+
+    // Collects modifier argument tokens.
+    // Next modifier keyword is only recognized after argument tokens.
+    (@mod $mode:tt [$($p:tt)*] [$($m:tt)*] $k:ident [$($a:tt)*] => $($rest:tt)*) => {
+        $crate::array_impl!(@flush $mode [$($p)*] [$($m)*] $k [$($a)*] @elem [] $($rest)*)
+    };
+
+    (@mod $mode:tt [$($p:tt)*] [$($m:tt)*] $k:ident [$($a:tt)*] , $b:ident => $($rest:tt)*) => {
+        $crate::array_impl!(@flush $mode [$($p)*] [$($m)*] $k [$($a)*] @elem [$b] $($rest)*)
+    };
+
+    // Pattern guard follows the last modifier.
+    (@mod $mode:tt [$($p:tt)*] [$($m:tt)*] reversed [] if $($rest:tt)*) => {
+        $crate::array_impl!(@flush $mode [$($p)*] [$($m)*] reversed [] @guard [if] $($rest)*)
+    };
+
+    (@mod $mode:tt [$($p:tt)*] [$($m:tt)*] $k:ident [$($a:tt)+] if $($rest:tt)*) => {
+        $crate::array_impl!(@flush $mode [$($p)*] [$($m)*] $k [$($a)*] @guard [if] $($rest)*)
+    };
+
+    // `rev` takes no argument, so it is followed by the next modifier right away.
+    (@mod $mode:tt [$($p:tt)*] [$($m:tt)*] reversed [] $k:ident $($rest:tt)*) => {
+        $crate::array_impl!(@mod $mode [$($p)*] [$($m)* .reversed()] $k [] $($rest)*)
+    };
+
+    (@mod $mode:tt [$($p:tt)*] [$($m:tt)*] $k:ident [$($a:tt)+] from $($rest:tt)*) => {
+        $crate::array_impl!(@flush $mode [$($p)*] [$($m)*] $k [$($a)*] @mod from [] $($rest)*)
+    };
+
+    (@mod $mode:tt [$($p:tt)*] [$($m:tt)*] $k:ident [$($a:tt)+] rev $($rest:tt)*) => {
+        $crate::array_impl!(@flush $mode [$($p)*] [$($m)*] $k [$($a)*] @mod reversed [] $($rest)*)
+    };
+
+    (@mod $mode:tt [$($p:tt)*] [$($m:tt)*] $k:ident [$($a:tt)+] by $($rest:tt)*) => {
+        $crate::array_impl!(@flush $mode [$($p)*] [$($m)*] $k [$($a)*] @mod by [] $($rest)*)
+    };
+
+    (@mod $mode:tt [$($p:tt)*] [$($m:tt)*] $k:ident [$($a:tt)+] down_from $($rest:tt)*) => {
+        $crate::array_impl!(@flush $mode [$($p)*] [$($m)*] $k [$($a)*] @mod down_from [] $($rest)*)
+    };
+
+    (@mod $mode:tt [$($p:tt)*] [$($m:tt)*] $k:ident [$($a:tt)*] $t:tt $($rest:tt)*) => {
+        $crate::array_impl!(@mod $mode [$($p)*] [$($m)*] $k [$($a)* $t] $($rest)*)
+    };
+
+    // Turns collected modifier into `Enumerator` method call and continues with next phase.
+    (@flush $mode:tt [$($p:tt)*] [$($m:tt)*] of_type [$($a:tt)*] @$next:ident $($rest:tt)*) => {
+        $crate::array_impl!(@$next $mode [$($p)*] [$($m)* .of_type($crate::PhantomData::<$($a)*>)] $($rest)*)
+    };
+
+    (@flush $mode:tt [$($p:tt)*] [$($m:tt)*] $k:ident [$($a:tt)*] @$next:ident $($rest:tt)*) => {
+        $crate::array_impl!(@$next $mode [$($p)*] [$($m)* .$k($($a)*)] $($rest)*)
+    };
+
+    // Collects pattern guard tokens and appends them to the pattern.
+    (@guard $mode:tt [$($p:tt)*] [$($m:tt)*] [$($g:tt)*] => $($rest:tt)*) => {
+        $crate::array_impl!(@elem $mode [$($p)* $($g)*] [$($m)*] [] $($rest)*)
+    };
+
+    (@guard $mode:tt [$($p:tt)*] [$($m:tt)*] [$($g:tt)*] , $b:ident => $($rest:tt)*) => {
+        $crate::array_impl!(@elem $mode [$($p)* $($g)*] [$($m)*] [$b] $($rest)*)
+    };
+
+    (@guard $mode:tt [$($p:tt)*] [$($m:tt)*] [$($g:tt)*] $t:tt $($rest:tt)*) => {
+        $crate::array_impl!(@guard $mode [$($p)*] [$($m)*] [$($g)* $t] $($rest)*)
+    };
+
+    // Must precede generic element expression as `yield` is not allowed there.
+    (@elem $mode:tt [$($p:tt)*] [$($m:tt)*] [$($b:ident)?] yield $e:expr ; $($rest:tt)+) => {
+        $crate::array_impl!(@clause $mode [$($m)*] {[[[$($p)*] [$e]]] [$($b)?] [yield]} [] [] [] [] $($rest)+)
+    };
+
+    (@elem $mode:tt [$($p:tt)*] [$($m:tt)*] [$($b:ident)?] $e:expr ; $($rest:tt)+) => {
+        $crate::array_impl!(@clause $mode [$($m)*] {[[[$($p)*] [$e]]] [$($b)?] []} [] [] [] [] $($rest)+)
+    };
+
+    (@elem $mode:tt [$($p:tt)*] [$($m:tt)*] [$($b:ident)?] $e:expr , $($rest:tt)+) => {
+        $crate::array_impl!(@arm $mode [$($m)*] [$($b)?] [[[$($p)*] [$e]]] [] $($rest)+)
+    };
+
+    // Collects match-like arms following the first one.
+    (@arm $mode:tt [$($m:tt)*] [$($b:ident)?] [$($arms:tt)*] [$($p:tt)*] => $e:expr , $($rest:tt)+) => {
+        $crate::array_impl!(@arm $mode [$($m)*] [$($b)?] [$($arms)* [[$($p)*] [$e]]] [] $($rest)+)
+    };
+
+    (@arm $mode:tt [$($m:tt)*] [$($b:ident)?] [$($arms:tt)*] [$($p:tt)*] => $e:expr ; $($rest:tt)+) => {
+        $crate::array_impl!(@clause $mode [$($m)*] {[$($arms)* [[$($p)*] [$e]]] [$($b)?] []} [] [] [] [] $($rest)+)
+    };
+
+    (@arm $mode:tt [$($m:tt)*] [$($b:ident)?] [$($arms:tt)*] [$($p:tt)*] $t:tt $($rest:tt)+) => {
+        $crate::array_impl!(@arm $mode [$($m)*] [$($b)?] [$($arms)*] [$($p)* $t] $($rest)+)
+    };
+
+    // Collects clauses between element expression and array length.
+    // Must precede generic `where` as `after |e| c` parses as an expression.
+    (@clause $mode:tt [$($m:tt)*] {$($h:tt)*} [$($cond:expr,)*] [$($name:expr,)*] [$($post:tt)*] [$($whole:tt)*] where after |$pb:pat_param| $pc:expr ; $($rest:tt)+) => {
+        $crate::array_impl!(@clause $mode [$($m)*] {$($h)*} [$($cond,)*] [$($name,)*] [$($post)* (after $pb => $pc)] [$($whole)*] $($rest)+)
+    };
+
+    // Fallible predicates are unwrapped same as element expression.
+    (@clause (result $l:lifetime) [$($m:tt)*] {$($h:tt)*} [$($cond:expr,)*] [$($name:expr,)*] [$($post:tt)*] [$($whole:tt)*] where try $( $c:expr ),+ ; $($rest:tt)+) => {
+        $crate::array_impl!(@clause (result $l) [$($m)*] {$($h)*} [$($cond,)* $($crate::array_impl!(@unwrap (result $l) $c),)+] [$($name,)* $(stringify!($c),)+] [$($post)*] [$($whole)*] $($rest)+)
+    };
+
+    (@clause $mode:tt [$($m:tt)*] {$($h:tt)*} [$($cond:expr,)*] [$($name:expr,)*] [$($post:tt)*] [$($whole:tt)*] where $( $c:expr ),+ ; $($rest:tt)+) => {
+        $crate::array_impl!(@clause $mode [$($m)*] {$($h)*} [$($cond,)* $($c,)+] [$($name,)* $(stringify!($c),)+] [$($post)*] [$($whole)*] $($rest)+)
+    };
+
+    (@clause $mode:tt [$($m:tt)*] {$($h:tt)*} [$($cond:expr,)*] [$($name:expr,)*] [$($post:tt)*] [$($whole:tt)*] distinct ; $($rest:tt)+) => {
+        $crate::array_impl!(@clause $mode [$($m)*] {$($h)*} [$($cond,)*] [$($name,)*] [$($post)* (distinct)] [$($whole)*] $($rest)+)
+    };
+
+    (@clause $mode:tt [$($m:tt)*] {$($h:tt)*} [$($cond:expr,)*] [$($name:expr,)*] [$($post:tt)*] [$($whole:tt)*] sorted by $k:expr ; $($rest:tt)+) => {
+        $crate::array_impl!(@clause $mode [$($m)*] {$($h)*} [$($cond,)*] [$($name,)*] [$($post)*] [$($whole)* (sorted by $k)] $($rest)+)
+    };
+
+    (@clause $mode:tt [$($m:tt)*] {$($h:tt)*} [$($cond:expr,)*] [$($name:expr,)*] [$($post:tt)*] [$($whole:tt)*] sorted ; $($rest:tt)+) => {
+        $crate::array_impl!(@clause $mode [$($m)*] {$($h)*} [$($cond,)*] [$($name,)*] [$($post)*] [$($whole)* (sorted)] $($rest)+)
+    };
+
+    (@clause $mode:tt [$($m:tt)*] {$($h:tt)*} [$($cond:expr,)*] [$($name:expr,)*] [$($post:tt)*] [$($whole:tt)*] step $s:expr ; $($rest:tt)+) => {
+        $crate::array_impl!(@clause $mode [$($m)* .by($s)] {$($h)*} [$($cond,)*] [$($name,)*] [$($post)*] [$($whole)*] $($rest)+)
+    };
+
+    // Reverses enumeration and writes elements back-to-front.
+    // Kept first in the list of whole-array clauses to select the builder.
+    (@clause $mode:tt [$($m:tt)*] {$($h:tt)*} [$($cond:expr,)*] [$($name:expr,)*] [$($post:tt)*] [$($whole:tt)*] fill rev ; $($rest:tt)+) => {
+        $crate::array_impl!(@clause $mode [$($m)* .fill_rev()] {$($h)*} [$($cond,)*] [$($name,)*] [$($post)*] [(fill rev) $($whole)*] $($rest)+)
+    };
+
+    (@clause $mode:tt [$($m:tt)*] {$($h:tt)*} [$($cond:expr,)*] [$($name:expr,)*] [$($post:tt)*] [$($whole:tt)*] rev ; $($rest:tt)+) => {
+        $crate::array_impl!(@clause $mode [$($m)* .reversed()] {$($h)*} [$($cond,)*] [$($name,)*] [$($post)*] [$($whole)*] $($rest)+)
+    };
+
+    (@clause panic [$($m:tt)*] {$($h:tt)*} [$($cond:expr,)*] [$($name:expr,)*] [$($post:tt)*] [$($whole:tt)*] else $f:expr ; $($rest:tt)+) => {
+        $crate::array_impl!(@clause [$f] [$($m)*] {$($h)*} [$($cond,)*] [$($name,)*] [$($post)*] [$($whole)*] $($rest)+)
+    };
+
+    // Labels enumeration loop, so element expression may skip to the next value.
+    (@clause $mode:tt [$($m:tt)*] {$($h:tt)*} [$($cond:expr,)*] [$($name:expr,)*] [$($post:tt)*] [$($whole:tt)*] retry $l:lifetime ; $($rest:tt)+) => {
+        $crate::array_impl!(@clause $mode [$($m)*] {$($h)* [$l]} [$($cond,)*] [$($name,)*] [$($post)*] [$($whole)*] $($rest)+)
+    };
+
+    (@clause $mode:tt [$($m:tt)*] {$($h:tt)*} [$($cond:expr,)*] [$($name:expr,)*] [$($post:tt)*] [$($whole:tt)*] tries $t:expr ; $($rest:tt)+) => {
+        $crate::array_impl!(@clause $mode [$($m)* .tries($t)] {$($h)*} [$($cond,)*] [$($name,)*] [$($post)*] [$($whole)*] $($rest)+)
+    };
+
+    // Array length may be inferred from the expected type.
+    (@clause $mode:tt [$($m:tt)*] {$($h:tt)*} $conds:tt $names:tt [$($post:tt)*] [$($whole:tt)*] _) => {
+        $crate::array_impl!(@build $mode [$($m)*] {$($h)*} $conds $names [$($post)*] [$($whole)*] [])
+    };
+
+    (@clause $mode:tt [$($m:tt)*] {$($h:tt)*} $conds:tt $names:tt [$($post:tt)*] [$($whole:tt)*] $n:expr) => {
+        $crate::array_impl!(@build $mode [$($m)*] {$($h)*} $conds $names [$($post)*] [$($whole)*] [$n])
+    };
+
+    // Selects builder that writes elements front-to-back or back-to-front.
+    (@build $mode:tt $m:tt $h:tt $conds:tt $names:tt $post:tt [(fill rev) $($whole:tt)*] $n:tt) => {
+        $crate::array_impl!(@build $mode $m $h $conds $names $post [$($whole)*] $n [rev] write_back)
+    };
+
+    (@build $mode:tt $m:tt $h:tt $conds:tt $names:tt $post:tt $whole:tt $n:tt) => {
+        $crate::array_impl!(@build $mode $m $h $conds $names $post $whole $n [] write)
+    };
+
+    (@build $mode:tt [$($m:tt)*] {[$([[$($p:tt)*] [$e:expr]])+] [$($b:ident)?] [$($batch:tt)?] $([$l:lifetime])?} $conds:tt $names:tt [$($post:tt)*] [$($whole:tt)*] [$($n:expr)?] [$($rev:ident)?] $write:ident) => {{
+        #[allow(unused_mut)]
+        let mut array = $crate::array_impl!(@new $mode [$($rev)?] [$($n)?]);
+
+        let mut enumerator = $crate::Enumerator::new(0, 1) $($m)*;
+        enumerator.prepare(array.remaining());
+
+        // Cleared when pattern rejects a value.
+        #[allow(unused_mut)]
+        let mut exact = $crate::array_impl!(@exact $conds [$($post)*] [$($batch)?] [$([$e])+] $([$l])?);
+
+        // Typed loop value rejects `break` with retry label.
+        let _: $crate::DontBreakFromElementExpressionWithoutLabel = $($l:)? loop {
+            if array.is_full() {
+                // This is the only way ouf of the loop without leaving outer scope.
+                break $crate::DontBreakFromElementExpressionWithoutLabel;
+            }
+
+            let value = match $crate::Iterator::next(&mut enumerator) {
+                Some(value) => value,
+                None => $crate::array_impl!(@exhausted $mode enumerator array $names),
+            };
+
+            $(
+                #[allow(unused_variables)]
+                let $b = array.as_initialized_slice();
+            )?
+
+            #[allow(unreachable_code)]
+            {
+                #[allow(unused_variables)]
+                let elem = match value {
+                    $(
+                        $($p)* => $crate::array_impl!(@body $conds $crate::array_impl!(@unwrap $mode $e)),
+                    )+
+                    #[allow(unreachable_patterns)]
+                    _ => {
+                        exact = false;
+                        continue;
+                    }
+                };
+
+                $crate::array_impl!(@write [$($batch)?] [$($post)*] array $write elem);
+            }
+        };
+
+        if exact {
+            enumerator.exact(array.len());
+        }
+
+        $crate::array_impl!(@fill $mode array $write);
+        $( $crate::array_impl!(@after $whole array); )*
+        $crate::array_impl!(@finish $mode array)
+    }};
+
+    // Enumeration with single arm and without filtering clauses must use every value.
+    (@exact [] [] [] [$arm:tt]) => {
+        true
+    };
+
+    (@exact $conds:tt $post:tt $batch:tt $arms:tt $($l:tt)?) => {
+        false
+    };
+
+    // Unwraps `Ok` value of the element expression or breaks out with `Err`.
+    (@unwrap (result $l:lifetime) $e:expr) => {
+        match $e {
+            Ok(value) => value,
+            Err(err) => break $l Err(err),
+        }
+    };
+
+    (@unwrap $mode:tt $e:expr) => {
+        $e
+    };
+
+    // Checks post-conditions and writes constructed element.
+    (@write [] [$($post:tt)*] $array:ident $write:ident $elem:ident) => {
+        $(
+            #[allow(unused_variables, clippy::diverging_sub_expression)]
+            #[warn(unreachable_code)]
+            let cond = $crate::array_impl!(@post $post $array $elem);
+
+            if <bool as $crate::Not>::not(cond) { continue; }
+        )*
+
+        unsafe {
+            $array.$write($elem);
+        }
+    };
+
+    // Writes batch elements one by one until array is full.
+    // Excess elements are dropped with the batch iterator.
+    (@write [yield] [$($post:tt)*] $array:ident $write:ident $elem:ident) => {
+        for $elem in $crate::IntoIterator::into_iter($elem) {
+            if $array.is_full() {
+                break;
+            }
+
+            $crate::array_impl!(@write [] [$($post)*] $array $write $elem);
+        }
+    };
+
+    // Checks predicates and evaluates element expression for matched enumerator value.
+    (@body [$($cond:expr,)*] $e:expr) => {{
+        $(
+            #[allow(unused_variables, clippy::diverging_sub_expression)]
+            #[warn(unreachable_code)]
+            let cond = $cond;
+
+            if <bool as $crate::Not>::not(cond) { continue; }
+        )*
+
+        #[allow(unused_variables)]
+        let elem;
+
+        #[allow(unused_variables)]
+        let dont_continue_in_element_expression_without_label;
+
+        loop {
+            #[allow(unused)]
+            {
+                dont_continue_in_element_expression_without_label = ();
+            }
+
+            #[allow(unused_variables, clippy::diverging_sub_expression)]
+            #[warn(unreachable_code)]
+            let value = $e;
+
+            elem = value;
+
+            break $crate::DontBreakFromElementExpressionWithoutLabel;
+        };
+
+        elem
+    }};
+
+    (@clone [$($n:expr)?] $v:expr) => {{
+        let mut array = $crate::builder::PartiallyInitArray $(::<_, $n>)? ::uninit();
+        array.fill_cloned($v);
+
+        unsafe {
+            // SAFETY: `fill_cloned` initializes all remaining elements.
+            array.assume_init()
+        }
+    }};
+
+    (@post (after $pb:pat_param => $pc:expr) $array:ident $elem:ident) => {{
+        let $pb = &$elem;
+        $pc
+    }};
+
+    (@post (distinct) $array:ident $elem:ident) => {
+        <bool as $crate::Not>::not($array.as_initialized_slice().contains(&$elem))
+    };
+
+    // Fills array from source expression re-evaluated for each element.
+    (@while [$p:pat] [$src:expr] [$e:expr] $conds:tt $n:expr) => {{
+        #[allow(unused_mut)]
+        let mut array = $crate::builder::PartiallyInitArray::<_, $n>::uninit();
+
+        loop {
+            if array.is_full() {
+                break;
+            }
+
+            #[allow(unreachable_code)]
+            {
+                #[allow(unused_variables)]
+                let elem = match $src {
+                    $p => $crate::array_impl!(@body $conds $e),
+                    #[allow(unreachable_patterns)]
+                    _ => break,
+                };
+
+                unsafe {
+                    array.write(elem);
+                }
+            }
+        }
+
+        array.try_init()
+    }};
+
+    (@new boxed [rev] [$($n:expr)?]) => {
+        $crate::builder::BoxedRevPartiallyInitArray $(::<_, { $n }>)? ::uninit()
+    };
+
+    (@new boxed [] [$($n:expr)?]) => {
+        $crate::builder::BoxedPartiallyInitArray $(::<_, { $n }>)? ::uninit()
+    };
+
+    // Splits list into comma-separated items. The last one is fill expression.
+    (@list $mode:tt [$($i:tt)*] [$($c:tt)+] , $($rest:tt)+) => {
+        $crate::array_impl!(@list $mode [$($i)* [$($c)+]] [] $($rest)+)
+    };
+
+    (@list $mode:tt [$($i:tt)*] [$($c:tt)+] ; _) => {
+        $crate::array_impl!(@fill_list $mode [$($i)*] [$($c)+] [])
+    };
+
+    (@list $mode:tt [$($i:tt)*] [$($c:tt)+] ; $n:expr) => {
+        $crate::array_impl!(@fill_list $mode [$($i)*] [$($c)+] [$n])
+    };
+
+    (@list $mode:tt [$($i:tt)*] [$($c:tt)*] $t:tt $($rest:tt)+) => {
+        $crate::array_impl!(@list $mode [$($i)*] [$($c)* $t] $($rest)+)
+    };
+
+    // Writes listed items, then evaluates fill expression for each remaining element.
+    (@fill_list $mode:tt [$($i:tt)*] [$f:expr] [$($n:expr)?]) => {{
+        let mut array = $crate::array_impl!(@new $mode [] [$($n)?]);
+        $crate::array_impl!(@check $mode array $crate::ListLen::<{ 0 $(+ $crate::array_impl!(@count $i))* }>);
+        $( $crate::array_impl!(@item $mode array $i); )*
+        $crate::array_impl!(@fill [$f] array write);
+        $crate::array_impl!(@finish $mode array)
+    }};
+
+    // `..` separating listed elements from fill expression.
+    (@item $mode:tt $array:ident [..]) => {};
+
+    // Moves elements of spread array.
+    // Spread must be an array, so its length can't lie.
+    (@item $mode:tt $array:ident [.. $a:expr]) => {{
+        let spread = $a;
+        $crate::array_impl!(@check $mode $array $crate::ListLen::of(&spread));
+        assert!(
+            <[_]>::len(&spread) <= $array.remaining(),
+            "Failed to spread {} elements, only {} elements left",
+            <[_]>::len(&spread),
+            $array.remaining(),
+        );
+
+        for value in spread {
+            unsafe {
+                // SAFETY: Length checked above.
+                $array.write(value);
+            }
+        }
+    }};
+
+    (@item $mode:tt $array:ident [$e:expr]) => {{
+        assert!(!$array.is_full(), "Too many listed elements for array length");
+        let value = $e;
+        unsafe {
+            // SAFETY: Not fully initialized.
+            $array.write(value);
+        }
+    }};
+
+    (@count [..]) => { 0 };
+    (@count [.. $a:expr]) => { 0 };
+    (@count [$e:expr]) => { 1 };
+
+    (@check boxed $array:ident $len:expr) => {
+        $len.check_boxed(&$array)
+    };
+
+    (@check vec $array:ident $len:expr) => {
+        $len.check_vec(&$array)
+    };
+
+    (@check $mode:tt $array:ident $len:expr) => {
+        $len.check(&$array)
+    };
+
+    (@new vec [rev] [$n:expr]) => {
+        $crate::builder::RevPartiallyInitVec::with_len($n)
+    };
+
+    (@new vec [] [$n:expr]) => {
+        $crate::builder::PartiallyInitVec::with_len($n)
+    };
+
+    (@new vec $rev:tt []) => {
+        compile_error!("Length of the vector must be specified")
+    };
+
+    (@new $mode:tt [rev] [$($n:expr)?]) => {
+        $crate::builder::RevPartiallyInitArray $(::<_, { $n }>)? ::uninit()
+    };
+
+    (@new $mode:tt [] [$($n:expr)?]) => {
+        $crate::builder::PartiallyInitArray $(::<_, { $n }>)? ::uninit()
+    };
+
+    (@exhausted option $enumerator:ident $array:ident $names:tt) => {
+        break $crate::DontBreakFromElementExpressionWithoutLabel
+    };
+
+    (@exhausted [$f:expr] $enumerator:ident $array:ident $names:tt) => {
+        break $crate::DontBreakFromElementExpressionWithoutLabel
+    };
+
+    (@exhausted $mode:tt $enumerator:ident $array:ident [$($name:expr,)*]) => {
+        $enumerator.exhausted(
+            $array.len(),
+            $array.len() + $array.remaining(),
+            &[$($name),*],
+        )
+    };
+
+    (@finish boxed $array:ident) => {
+        unsafe {
+            // SAFETY: `is_full` returned true.
+            $array.assume_init()
+        }
+    };
+
+    (@finish vec $array:ident) => {
+        unsafe {
+            // SAFETY: `is_full` returned true.
+            $array.assume_init()
+        }
+    };
+
+    (@finish (result $l:lifetime) $array:ident) => {
+        Ok($crate::array_impl!(@finish panic $array))
+    };
+
+    (@finish panic $array:ident) => {
+        #[allow(unknown_lints, never_type_fallback_flowing_into_unsafe)]
+        unsafe {
+            // SAFETY: `is_full` returned true.
+            $array.assume_init()
+        }
+    };
+
+    (@finish option $array:ident) => {
+        $array.try_init()
+    };
+
+    (@finish [$f:expr] $array:ident) => {
+        $crate::array_impl!(@finish panic $array)
+    };
+
+    // Fills elements that enumeration failed to construct.
+    (@fill [$f:expr] $array:ident $write:ident) => {
+        while !$array.is_full() {
+            #[allow(unreachable_code)]
+            {
+                #[allow(unused_variables, clippy::diverging_sub_expression)]
+                #[warn(unreachable_code)]
+                let value = $f;
+
+                unsafe {
+                    // SAFETY: Not fully initialized.
+                    $array.$write(value);
+                }
+            }
+        }
+    };
+
+    (@fill $mode:tt $array:ident $write:ident) => {};
+
+    // Applies clauses that affect constructed elements as a whole.
+    (@after (sorted) $array:ident) => {
+        if $array.is_full() {
+            $array.as_initialized_slice_mut().sort_unstable()
+        }
+    };
+
+    (@after (sorted by $k:expr) $array:ident) => {
+        if $array.is_full() {
+            $array.as_initialized_slice_mut().sort_unstable_by_key($k)
+        }
+    };
+}
+
+/// Constructs arrays by repeating expression
+/// with elements from iterators bound to provided patterns.
+///
+/// Creating arrays from iterators is really handy.
+/// But it comes at price - there could be not enough values in the iterator to fill the array.
+///
+/// Therefore this macro returns `Option`.
+/// `Some` array is returned if there were enough values.
+/// Otherwise `None` is returned.
+///
+/// ```
+/// # use array_fu::collect_array;
+/// let opt = collect_array![1..; 3];
+///
+/// assert_eq!(opt, Some([1, 2, 3]));
+/// ```
+///
+/// `None` is returned otherwise.
+///
+/// ```
+/// # use array_fu::collect_array;
+/// let opt = collect_array![1..3; 3];
+///
+/// assert_eq!(opt, None, "There's only two elements in 1..3");
+/// ```
+///
+/// Similarly to `array!` macro, `collect_array` can be given a pattern to bind iterator elements
+/// and expression to produce array elements.
+///
+/// ```
+/// # use array_fu::collect_array;
+/// let opt = collect_array![x in 1.. => x / 2; 3];
+///
+/// assert_eq!(opt, Some([0, 1, 1]));
+/// ```
+///
+/// But why stop there? Multiple iterators can be collected into an array!
+///
+/// ```
+/// # use array_fu::collect_array;
+/// let opt = collect_array![x in 1.., y in 2.. => x + y; 3];
+///
+/// assert_eq!(opt, Some([3, 5, 7]));
+/// ```
+///
+/// Iterators are zipped in order, so `a in x, b in y, c in z` matches items of
+/// `x.zip(y).zip(z)` against `((a, b), c)` pattern.
+/// Alternatively flat tuple pattern may be matched against tuple of iterators,
+/// `(a, b, c) in (x, y, z)` is the same as above.
+/// Each element of the pattern is matched against its own iterator.
+/// Rest pattern `..` matches any number of iterators in the middle.
+///
+/// ```
+/// # use array_fu::collect_array;
+/// let opt = collect_array![(x, 0, z) in (1.., [0, 1, 0, 0], "abc".chars()) => (x, z); 2];
+///
+/// assert_eq!(opt, Some([(1, 'a'), (3, 'c')]));
+///
+/// let opt = collect_array![(x, .., z) in (1.., 2.., 3.., 4..) => x + z; 2];
+///
+/// assert_eq!(opt, Some([5, 7]));
+/// ```
+///
+/// Surely it also supports predicates.
+/// When predicate evaluates to `false`, next items are taken from all iterators.
+///
+/// ```
+/// # use array_fu::collect_array;
+/// let opt = collect_array![x in 1.., y in 2.. => x + y; where x * y > 10; 3];
+///
+/// assert_eq!(opt, Some([7, 9, 11]));
+/// ```
+///
+/// Patterns support destructuring.
+///
+/// ```
+/// # use array_fu::collect_array;
+/// let values = collect_array![(x, y) in [(1, 2), (3, 4), (5, 6)] => x + y; 3];
+///
+/// assert_eq!(values, Some([3, 7, 11]));
+/// ```
+///
+/// And patterns don't have to be irrefutable.
+///
+/// ```
+/// # use array_fu::collect_array;
+/// let values = collect_array![(1, y) in [(1, 2), (3, 4), (1, 6)] => y; 2];
+///
+/// assert_eq!(values, Some([2, 6]));
+/// ```
+///
+/// Patterns may have guards. Failed guard skips values like predicate does.
+///
+/// ```
+/// # use array_fu::collect_array;
+/// let values = collect_array![(a, b) if a < b in [(1, 3), (4, 2), (2, 7)] => b - a; 2];
+///
+/// assert_eq!(values, Some([2, 5]));
+/// ```
+///
+/// ## Index
+///
+/// Index of the element being constructed can be bound to a name
+/// provided after iterators: `$pat in $iter; $name => $expr`.
+/// It is the number of elements constructed so far,
+/// so values skipped by predicates do not advance it.
+///
+/// ```
+/// # use array_fu::collect_array;
+/// let values = collect_array![x in 1..; i => x * 10 + i; where x % 2 == 0; 3];
+///
+/// assert_eq!(values, Some([20, 41, 62]));
+/// ```
+///
+/// Alternatively index name may precede iterators: `$name, $pat in $iter => $expr`.
+///
+/// ```
+/// # use array_fu::collect_array;
+/// let values = collect_array![i, x in ["a", "b", "c"] => (i, x); 3];
+///
+/// assert_eq!(values, Some([(0, "a"), (1, "b"), (2, "c")]));
+/// ```
+///
+/// ## Fallback
+///
+/// `else |$index| $expr` clause provides elements when iterators run out of values.
+/// Fallback expression is evaluated for each missing element with its index bound to the pattern.
+/// Array is always filled in this case, so it is returned without `Option`.
+///
+/// ```
+/// # use array_fu::collect_array;
+/// let values = collect_array![x in [1, 2] => x; else |i| i * 10; 4];
+///
+/// assert_eq!(values, [1, 2, 20, 30]);
+/// ```
+///
+/// `or $expr` is a shorthand for fallback that does not need the index.
+///
+/// ```
+/// # use array_fu::collect_array;
+/// let values = collect_array![x in [1, 2] => x * 2; or 0; 4];
+///
+/// assert_eq!(values, [2, 4, 0, 0]);
+/// ```
+///
+/// ## Retry
+///
+/// `retry $label` clause labels iteration loop, as with [`array!`].
+/// `continue $label` discards current values and takes next ones from iterators.
+/// Unlike [`array!`], `break $label` is allowed and stops taking values,
+/// so array is completed by `else` clause or `None` is returned.
+///
+/// ```
+/// # use array_fu::collect_array;
+/// let values = collect_array![s in ["1", "x", "3", "4"] => match s.parse::<u32>() {
+///     Ok(value) => value,
+///     Err(_) => continue 'skip,
+/// }; retry 'skip; 3];
+///
+/// assert_eq!(values, Some([1, 3, 4]));
+///
+/// let values = collect_array![s in ["1", "", "3"] => match s.parse::<u32>() {
+///     Ok(value) => value,
+///     Err(_) => break 'stop,
+/// }; retry 'stop; else |_| 0; 3];
+///
+/// assert_eq!(values, [1, 0, 0]);
+/// ```
+///
+/// As with [`array!`], `yield $expr` writes every element of a batch.
+/// With `Option` batches `None` skips the item.
+///
+/// ```
+/// # use array_fu::collect_array;
+/// let values = collect_array![s in "1 x 3 4".split(' ') => yield s.parse::<u32>().ok(); 3];
+///
+/// assert_eq!(values, Some([1, 3, 4]));
+/// ```
+///
+/// `sorted` and `sorted by $key` clauses sort collected array, as with [`array!`].
+/// Nothing is sorted when there are not enough values.
+///
+/// ```
+/// # use array_fu::collect_array;
+/// let values = collect_array![x in [5, 3, 8, 1, 9] => x * 2; where x != 8; sorted; 4];
+///
+/// assert_eq!(values, Some([2, 6, 10, 18]));
+///
+/// let values = collect_array![["bb", "a", "ccc"]; sorted by |s| s.len(); 3];
+///
+/// assert_eq!(values, Some(["a", "bb", "ccc"]));
+/// ```
+#[macro_export]
 macro_rules! collect_array {
+    // Splits flat tuple pattern over tuple of iterators.
+    (($($p:tt)*) in ($ih:expr, $($it:expr),+ $(,)?) $($rest:tt)+) => {
+        $crate::collect_array_impl!(@flat [] [] [$($p)*] [[$ih] $([$it])+] $($rest)+)
+    };
+
+    ($it:expr; or $f:expr ; $n:expr) => {
+        $crate::collect_array!(e in $it => e ; else |_| $f ; $n)
+    };
+
+    ($it:expr; sorted $(by $k:expr)? ; $n:expr) => {
+        $crate::collect_array!(e in $it => e ; sorted $(by $k)? ; $n)
+    };
+
+    ($it:expr; $n:expr) => {
+        $crate::collect_array!(e in $it => e ; $n)
+    };
+
+    ($it:expr; else |$fi:pat_param| $f:expr ; $n:expr) => {
+        $crate::collect_array!(e in $it => e ; else |$fi| $f ; $n)
+    };
+
+    ($e:expr; $ph:pat in $ih:expr $( , $pt:pat in $it:expr )* $(; where $($cond:expr),+ )? $(; else |$fi:pat_param| $f:expr )? ; $n:expr) => {
+        $crate::collect_array_impl!(@option [$($fi => $f)?] [] $e; $ph in $ih $( , $pt in $it )* $( ; where $($cond),+ )? ; $n)
+    };
+
+    // Must precede generic pattern form as `yield` is not allowed in element expression.
+    ($( $p:pat in $i:expr ),+ => yield $e:expr $(; where $($cond:expr),+ )? ; $n:expr) => {
+        $crate::collect_array_impl!(@option [] [] @batch $e; $($p in $i),+ $( ; where $($cond),+ )? ; $n)
+    };
+
+    // Sorts only successfully collected array.
+    ($( $p:pat in $i:expr ),+ => $e:expr $(; where $($cond:expr),+ )? ; sorted $(by $k:expr)? ; $n:expr) => {
+        match $crate::collect_array!($($p in $i),+ => $e $( ; where $($cond),+ )? ; $n) {
+            Some(mut array) => {
+                $crate::collect_array_impl!(@sort array $($k)?);
+                Some(array)
+            }
+            None => None,
+        }
+    };
+
+    // Labels iteration loop, so element expression may skip values or stop early.
+    ($( $p:pat in $i:expr ),+ => $e:expr $(; where $($cond:expr),+ )? ; retry $l:lifetime $(; else |$fi:pat_param| $f:expr )? ; $n:expr) => {
+        $crate::collect_array_impl!(@option [$($fi => $f)?] [] $e; $($p in $i),+ $( ; where $($cond),+ )? ; @retry $l ; $n)
+    };
+
+    ($( $p:pat in $i:expr ),+ => $e:expr $(; where $($cond:expr),+ )? ; or $f:expr ; $n:expr) => {
+        $crate::collect_array!($($p in $i),+ => $e $( ; where $($cond),+ )? ; else |_| $f ; $n)
+    };
+
+    ($( $p:pat in $i:expr ),+ => $e:expr $(; where $($cond:expr),+ )? $(; else |$fi:pat_param| $f:expr )? ; $n:expr) => {
+        $crate::collect_array!($e; $($p in $i),+ $( ; where $($cond),+ )? $( ; else |$fi| $f )? ; $n)
+    };
+
+    ($( $p:pat in $i:expr ),+ ; $idx:ident => $e:expr $(; where $($cond:expr),+ )? ; or $f:expr ; $n:expr) => {
+        $crate::collect_array!($($p in $i),+ ; $idx => $e $( ; where $($cond),+ )? ; else |_| $f ; $n)
+    };
+
+    ($( $p:pat in $i:expr ),+ ; $idx:ident => $e:expr $(; where $($cond:expr),+ )? $(; else |$fi:pat_param| $f:expr )? ; $n:expr) => {
+        $crate::collect_array_impl!(@option [$($fi => $f)?] [$idx] $e; $($p in $i),+ $( ; where $($cond),+ )? ; $n)
+    };
+
+    ($idx:ident , $( $p:pat in $i:expr ),+ => $e:expr $(; where $($cond:expr),+ )? ; or $f:expr ; $n:expr) => {
+        $crate::collect_array!($($p in $i),+ ; $idx => $e $( ; where $($cond),+ )? ; else |_| $f ; $n)
+    };
+
+    ($idx:ident , $( $p:pat in $i:expr ),+ => $e:expr $(; where $($cond:expr),+ )? $(; else |$fi:pat_param| $f:expr )? ; $n:expr) => {
+        $crate::collect_array!($($p in $i),+ ; $idx => $e $( ; where $($cond),+ )? $( ; else |$fi| $f )? ; $n)
+    };
+
+    ($($t:tt)+) => {
+        $crate::collect_array_impl!(@guard [] [] $($t)+)
+    };
+}
+
+/// Constructs arrays like [`collect_array!`] does, but gives back partially initialized array
+/// when iterators run out of values.
+///
+/// Syntax is identical to [`collect_array!`].
+/// Returns `Ok` array if there were enough values.
+/// Otherwise returns `Err` with [`PartiallyInitArray`] holding all elements constructed so far.
+///
+/// ```
+/// # use array_fu::try_collect_array;
+/// let result = try_collect_array![x in 1.. => x * 2; 3];
+///
+/// assert_eq!(result.ok(), Some([2, 4, 6]));
+/// ```
+///
+/// ```
+/// # use array_fu::try_collect_array;
+/// let result = try_collect_array![x in 1..3 => x * 2; 3];
+///
+/// let partial = result.unwrap_err();
+/// assert_eq!(partial.as_initialized_slice(), &[2, 4]);
+/// ```
+#[macro_export]
+macro_rules! try_collect_array {
+    ($it:expr; $n:expr) => {
+        $crate::try_collect_array!(e in $it => e ; $n)
+    };
+
+    ($e:expr; $ph:pat in $ih:expr $( , $pt:pat in $it:expr )* $(; where $($cond:expr),+ )? ; $n:expr) => {
+        $crate::collect_array_impl!(@result [] [] $e; $ph in $ih $( , $pt in $it )* $( ; where $($cond),+ )? ; $n)
+    };
+
+    ($( $p:pat in $i:expr ),+ => $e:expr $(; where $($cond:expr),+ )? ; $n:expr) => {
+        $crate::try_collect_array!($e; $($p in $i),+ $( ; where $($cond),+ )? ; $n)
+    };
+}
+
+/// Constructs arrays like [`collect_array!`] does,
+/// but fills elements missing due to iterators running out of values with `Default::default()`.
+///
+/// Syntax is identical to [`collect_array!`].
+/// Since array is always filled, it is returned as is, without wrapping into `Option`.
+///
+/// ```
+/// # use array_fu::collect_array_or_default;
+/// let values = collect_array_or_default![1..3; 5];
+///
+/// assert_eq!(values, [1, 2, 0, 0, 0]);
+/// ```
+///
+/// Predicates are applied only to values taken from iterators.
+///
+/// ```
+/// # use array_fu::collect_array_or_default;
+/// let values = collect_array_or_default![x in 1..10 => x; where x % 4 == 0; 3];
+///
+/// assert_eq!(values, [4, 8, 0]);
+/// ```
+#[macro_export]
+macro_rules! collect_array_or_default {
     ($it:expr; $n:expr) => {
-        $crate::collect_array!(e in $it => e ; $n)
+        $crate::collect_array_or_default!(e in $it => e ; $n)
+    };
+
+    ($e:expr; $ph:pat in $ih:expr $( , $pt:pat in $it:expr )* $(; where $($cond:expr),+ )? ; $n:expr) => {
+        $crate::collect_array_impl!(@option [_ => $crate::Default::default()] [] $e; $ph in $ih $( , $pt in $it )* $( ; where $($cond),+ )? ; $n)
+    };
+
+    ($( $p:pat in $i:expr ),+ => $e:expr $(; where $($cond:expr),+ )? ; $n:expr) => {
+        $crate::collect_array_or_default!($e; $($p in $i),+ $( ; where $($cond),+ )? ; $n)
+    };
+}
+
+/// Constructs arrays like [`collect_array!`] does, and returns iterator
+/// with values that were not consumed alongside the array.
+///
+/// Syntax is identical to [`collect_array!`].
+/// When multiple iterators are used, returned iterator yields tuples of their values, same as [`Iterator::zip`].
+///
+/// ```
+/// # use array_fu::collect_array_rest;
+/// let (header, mut body) = collect_array_rest![[1, 2, 3, 4, 5]; 2].unwrap();
+///
+/// assert_eq!(header, [1, 2]);
+/// assert_eq!(body.next(), Some(3));
+/// ```
+///
+/// `None` is returned if iterators run out of values.
+///
+/// ```
+/// # use array_fu::collect_array_rest;
+/// let opt = collect_array_rest![x in 1..3 => x * 2; 3];
+///
+/// assert!(opt.is_none());
+/// ```
+#[macro_export]
+macro_rules! collect_array_rest {
+    ($it:expr; $n:expr) => {
+        $crate::collect_array_rest!(e in $it => e ; $n)
+    };
+
+    ($e:expr; $ph:pat in $ih:expr $( , $pt:pat in $it:expr )* $(; where $($cond:expr),+ )? ; $n:expr) => {
+        $crate::collect_array_impl!(@rest [] [] $e; $ph in $ih $( , $pt in $it )* $( ; where $($cond),+ )? ; $n)
+    };
+
+    ($( $p:pat in $i:expr ),+ => $e:expr $(; where $($cond:expr),+ )? ; $n:expr) => {
+        $crate::collect_array_rest!($e; $($p in $i),+ $( ; where $($cond),+ )? ; $n)
+    };
+}
+
+/// Constructs arrays like [`collect_array!`] does, but always gives back
+/// [`PartiallyInitArray`] alongside a flag telling whether it was filled completely.
+///
+/// Syntax is identical to [`collect_array!`].
+///
+/// ```
+/// # use array_fu::collect_array_partial;
+/// let (values, full) = collect_array_partial![x in 1..3 => x * 2; 3];
+///
+/// assert!(!full);
+/// assert_eq!(values.as_initialized_slice(), &[2, 4]);
+///
+/// let (values, full) = collect_array_partial![1..; 3];
+///
+/// assert!(full);
+/// assert_eq!(values.try_init(), Some([1, 2, 3]));
+/// ```
+#[macro_export]
+macro_rules! collect_array_partial {
+    ($it:expr; $n:expr) => {
+        $crate::collect_array_partial!(e in $it => e ; $n)
+    };
+
+    ($e:expr; $ph:pat in $ih:expr $( , $pt:pat in $it:expr )* $(; where $($cond:expr),+ )? ; $n:expr) => {
+        $crate::collect_array_impl!(@partial [] [] $e; $ph in $ih $( , $pt in $it )* $( ; where $($cond),+ )? ; $n)
+    };
+
+    ($( $p:pat in $i:expr ),+ => $e:expr $(; where $($cond:expr),+ )? ; $n:expr) => {
+        $crate::collect_array_partial!($e; $($p in $i),+ $( ; where $($cond),+ )? ; $n)
+    };
+}
+
+/// Constructs arrays like [`collect_array!`] does, but requires iterators
+/// to have no values left after array is filled.
+///
+/// Syntax is identical to [`collect_array!`].
+/// Once array is filled, iterator is advanced once more
+/// and `None` is returned if it yields a value.
+/// That value is not checked against predicates.
+///
+/// ```
+/// # use array_fu::collect_array_exact;
+/// let values = collect_array_exact![[1, 2, 3]; 3];
+///
+/// assert_eq!(values, Some([1, 2, 3]));
+/// ```
+///
+/// ```
+/// # use array_fu::collect_array_exact;
+/// let values = collect_array_exact![x in [1, 2, 3, 4] => x * 2; 3];
+///
+/// assert_eq!(values, None);
+/// ```
+#[macro_export]
+macro_rules! collect_array_exact {
+    ($it:expr; $n:expr) => {
+        $crate::collect_array_exact!(e in $it => e ; $n)
+    };
+
+    ($e:expr; $ph:pat in $ih:expr $( , $pt:pat in $it:expr )* $(; where $($cond:expr),+ )? ; $n:expr) => {
+        $crate::collect_array_impl!(@exact [] [] $e; $ph in $ih $( , $pt in $it )* $( ; where $($cond),+ )? ; $n)
+    };
+
+    ($( $p:pat in $i:expr ),+ => $e:expr $(; where $($cond:expr),+ )? ; $n:expr) => {
+        $crate::collect_array_exact!($e; $($p in $i),+ $( ; where $($cond),+ )? ; $n)
+    };
+}
+
+/// Collects exactly `N` elements of an iterator into an array.
+///
+/// Unlike [`collect_array_exact!`] it panics instead of returning `None`
+/// if iterator yields fewer or more than `N` elements.
+/// Iterator is advanced exactly `N + 1` times, unless it runs out earlier.
+///
+/// ```
+/// # use array_fu::collect_exactly;
+/// let values = collect_exactly!([1, 2, 3, 4, 5, 6].chunks_exact(2); 3);
+///
+/// assert_eq!(values, [&[1, 2][..], &[3, 4], &[5, 6]]);
+/// ```
+///
+/// ```should_panic
+/// # use array_fu::collect_exactly;
+/// // Panics as iterator yields one element too many.
+/// let _: [u32; 2] = collect_exactly!(1..4; 2);
+/// ```
+#[macro_export]
+macro_rules! collect_exactly {
+    ($it:expr; _) => {
+        $crate::collect_exactly($crate::IntoIterator::into_iter($it))
+    };
+
+    ($it:expr; $n:expr) => {
+        $crate::collect_exactly::<_, { $n }>($crate::IntoIterator::into_iter($it))
+    };
+}
+
+/// Constructs arrays like [`collect_array!`] does, but only from endless iterators.
+///
+/// Iterators must implement [`EndlessIterator`](endless::EndlessIterator),
+/// so array is returned without wrapping into `Option`.
+/// Syntax is identical to [`collect_array!`] without fallback.
+///
+/// ```
+/// # use array_fu::collect_array_endless;
+/// let squares = collect_array_endless![x in 1.. => x * x; 4];
+///
+/// assert_eq!(squares, [1, 4, 9, 16]);
+///
+/// let mut next = 1;
+/// let powers = collect_array_endless![core::iter::repeat_with(|| { next *= 2; next }); 3];
+///
+/// assert_eq!(powers, [2, 4, 8]);
+///
+/// let values = collect_array_endless![(x, y) in (0.., [1, 2].iter().cycle()) => x * y; where x != 1; 3];
+///
+/// assert_eq!(values, [0, 2, 6]);
+/// ```
+///
+/// Iterators that may run out of values are rejected.
+///
+/// ```compile_fail
+/// # use array_fu::collect_array_endless;
+/// let _ = collect_array_endless![0..10; 4];
+/// ```
+#[macro_export]
+macro_rules! collect_array_endless {
+    ($it:expr; $n:expr) => {
+        $crate::collect_array_endless!(e in $it => e ; $n)
+    };
+
+    (($($p:tt)*) in ($ih:expr, $($it:expr),+ $(,)?) => $e:expr $(; where $($cond:expr),+ )? ; $n:expr) => {
+        $crate::collect_array_endless!(@unwrap ($($p)*) in ($crate::endless::endless($ih), $($crate::endless::endless($it)),+) => $e $( ; where $($cond),+ )? ; $n)
+    };
+
+    ($( $p:pat in $i:expr ),+ => $e:expr $(; where $($cond:expr),+ )? ; $n:expr) => {
+        $crate::collect_array_endless!(@unwrap $($p in $crate::endless::endless($i)),+ => $e $( ; where $($cond),+ )? ; $n)
+    };
+
+    (@unwrap $($t:tt)+) => {
+        match $crate::collect_array!($($t)+) {
+            Some(array) => array,
+            None => unreachable!("Endless iterator ran out of values"),
+        }
+    };
+}
+
+/// Takes first `N` elements of an iterator into an array.
+///
+/// Unlike [`collect_array!`] it panics instead of returning `None`
+/// if iterator yields fewer than `N` elements.
+/// Unlike [`collect_exactly!`] remaining elements are left in the iterator.
+///
+/// ```
+/// # use array_fu::array_from_iter;
+/// let mut bytes = [2u8, 7, 1, 2, 3].into_iter();
+/// let header: [u8; 2] = array_from_iter!(&mut bytes; _);
+///
+/// assert_eq!(header, [2, 7]);
+/// assert_eq!(bytes.len(), 3);
+/// ```
+///
+/// ```should_panic
+/// # use array_fu::array_from_iter;
+/// // Panics as only 3 of 4 elements are available.
+/// let _ = array_from_iter!(1..4; 4);
+/// ```
+#[macro_export]
+macro_rules! array_from_iter {
+    ($it:expr; _) => {
+        $crate::array_from_iter($crate::IntoIterator::into_iter($it))
+    };
+
+    ($it:expr; $n:expr) => {
+        $crate::array_from_iter::<_, { $n }>($crate::IntoIterator::into_iter($it))
+    };
+}
+
+/// Constructs array of `out` overlapping windows of `window` consecutive elements.
+///
+/// Windows are taken from iterator same as [`slice::windows`] does,
+/// each window starts one element after the previous one.
+/// Elements shared by several windows are cloned, so `window + out - 1` elements are consumed.
+/// Returns `None` if iterator runs out of elements before all windows are filled.
+///
+/// ```
+/// # use array_fu::array_windows;
+/// let windows = array_windows![1..; window = 3; out = 4];
+///
+/// assert_eq!(windows, Some([[1, 2, 3], [2, 3, 4], [3, 4, 5], [4, 5, 6]]));
+///
+/// let windows: Option<[[char; 2]; 2]> = array_windows!["abc".chars()];
+///
+/// assert_eq!(windows, Some([['a', 'b'], ['b', 'c']]));
+///
+/// assert_eq!(array_windows![[1, 2, 3]; window = 2; out = 3], None);
+/// ```
+///
+/// Window size must be non-zero.
+///
+/// ```should_panic
+/// # use array_fu::array_windows;
+/// let _ = array_windows![1..; window = 0; out = 2];
+/// ```
+///
+/// `array_windows!($array, $window)` takes windows of references into array.
+/// Number of windows is inferred and checked at compile time,
+/// so result is not wrapped into `Option`.
+///
+/// ```
+/// # use array_fu::array_windows;
+/// let signal = [1, 4, 2, 8];
+/// let windows: [[&i32; 2]; 3] = array_windows!(&signal, 2);
+///
+/// assert_eq!(windows, [[&1, &4], [&4, &2], [&2, &8]]);
+/// ```
+///
+/// ```compile_fail
+/// # use array_fu::array_windows;
+/// let _: [[&i32; 2]; 2] = array_windows!(&[1, 4, 2, 8], 2);
+/// ```
+#[macro_export]
+macro_rules! array_windows {
+    ($src:expr) => {
+        $crate::array_windows($crate::IntoIterator::into_iter($src))
+    };
+
+    ($src:expr, $w:expr) => {
+        $crate::array_ref_windows($src, $crate::WindowLen::<{ $w }>)
+    };
+
+    ($src:expr; window = $w:expr; out = $k:expr) => {
+        $crate::array_windows::<_, { $w }, { $k }>($crate::IntoIterator::into_iter($src))
+    };
+}
+
+/// Constructs array of `out` disjoint chunks of `chunk` consecutive elements.
+///
+/// Exactly `chunk * out` elements are consumed, remaining ones are left in the iterator.
+/// Returns `None` if iterator runs out of elements before all chunks are filled.
+/// Elements of incomplete chunk are dropped.
+///
+/// ```
+/// # use array_fu::array_chunks;
+/// let chunks = array_chunks![1..; chunk = 2; out = 3];
+///
+/// assert_eq!(chunks, Some([[1, 2], [3, 4], [5, 6]]));
+///
+/// let chunks: Option<[[char; 3]; 1]> = array_chunks!["abc".chars()];
+///
+/// assert_eq!(chunks, Some([['a', 'b', 'c']]));
+///
+/// assert_eq!(array_chunks![[1, 2, 3]; chunk = 2; out = 2], None);
+/// ```
+///
+/// `array_chunks!($array, $chunk)` takes chunks of array reference without copying,
+/// together with remainder slice shorter than a chunk.
+/// Number of chunks is inferred and checked at compile time.
+///
+/// ```
+/// # use array_fu::array_chunks;
+/// let bytes = [1u8, 2, 3, 4, 5];
+/// let (pairs, rest): ([&[u8; 2]; 2], _) = array_chunks!(&bytes, 2);
+///
+/// assert_eq!(pairs, [&[1, 2], &[3, 4]]);
+/// assert_eq!(rest, [5]);
+/// ```
+///
+/// ```compile_fail
+/// # use array_fu::array_chunks;
+/// let (_, _): ([&[u8; 2]; 3], _) = array_chunks!(&[1u8, 2, 3, 4, 5], 2);
+/// ```
+#[macro_export]
+macro_rules! array_chunks {
+    ($src:expr) => {
+        $crate::array_chunks($crate::IntoIterator::into_iter($src))
+    };
+
+    ($src:expr, $c:expr) => {
+        $crate::array_ref_chunks($src, $crate::ChunkLen::<{ $c }>)
+    };
+
+    ($src:expr; chunk = $c:expr; out = $k:expr) => {
+        $crate::array_chunks::<_, { $c }, { $k }>($crate::IntoIterator::into_iter($src))
+    };
+}
+
+/// Copies elements of a slice into an array.
+///
+/// Unlike [`collect_array!`] it panics instead of returning `None`
+/// if slice length is not equal to array length.
+///
+/// ```
+/// # use array_fu::collect_slice_array;
+/// let buffer = [1u8, 2, 3, 4, 5, 6];
+/// let (head, tail) = buffer.split_at(4);
+///
+/// assert_eq!(collect_slice_array![head; 4], [1, 2, 3, 4]);
+/// assert_eq!(collect_slice_array![tail; 2], [5, 6]);
+/// ```
+///
+/// ```should_panic
+/// # use array_fu::collect_slice_array;
+/// // Panics with "Failed to initialize array of 4 elements from slice of 3 elements".
+/// let _ = collect_slice_array![&[1, 2, 3]; 4];
+/// ```
+#[macro_export]
+macro_rules! collect_slice_array {
+    ($s:expr; $n:expr) => {
+        $crate::slice_array::<_, $n>($s)
+    };
+}
+
+/// Constructs arrays like [`collect_array!`] does, from iterator of `Result`s.
+///
+/// Pattern is bound to `Ok` values.
+/// Collection stops on the first `Err` which is returned as is.
+/// Otherwise `Ok` with the same `Option` as [`collect_array!`] would return is returned.
+///
+/// ```
+/// # use array_fu::collect_array_result;
+/// let values = collect_array_result![x in ["1", "2", "3"].map(str::parse::<i32>) => x * 2; 3];
+///
+/// assert_eq!(values, Ok(Some([2, 4, 6])));
+/// ```
+///
+/// ```
+/// # use array_fu::collect_array_result;
+/// let values = collect_array_result![["1", "x", "3"].map(str::parse::<i32>); 3];
+///
+/// assert!(values.is_err());
+/// ```
+///
+/// With `else |$index| $expr` clause missing elements are filled as in [`collect_array!`]
+/// and `Result<[T; N], E>` is returned, ready for `?` operator.
+///
+/// ```
+/// # use array_fu::collect_array_result;
+/// fn parse(s: &str) -> Result<[u8; 4], std::num::ParseIntError> {
+///     let values = collect_array_result![x in s.split('.').map(str::parse) => x; else |_| 0; 4]?;
+///     Ok(values)
+/// }
+///
+/// assert_eq!(parse("10.0.1"), Ok([10, 0, 1, 0]));
+/// assert!(parse("10.x.1").is_err());
+/// ```
+#[macro_export]
+macro_rules! collect_array_result {
+    ($it:expr; $n:expr) => {
+        $crate::collect_array_result!(e in $it => e ; $n)
+    };
+
+    ($e:expr; $p:pat in $i:expr $(; where $($cond:expr),+ )? $(; else |$fi:pat_param| $f:expr )? ; $n:expr) => {{
+        let mut error = None;
+        let array = $crate::collect_array!($e; $p in $crate::ResultShunt::new($i, &mut error) $( ; where $($cond),+ )? $( ; else |$fi| $f )? ; $n);
+        match error {
+            Some(err) => Err(err),
+            None => Ok(array),
+        }
+    }};
+
+    ($p:pat in $i:expr => $e:expr $(; where $($cond:expr),+ )? $(; else |$fi:pat_param| $f:expr )? ; $n:expr) => {
+        $crate::collect_array_result!($e; $p in $i $( ; where $($cond),+ )? $( ; else |$fi| $f )? ; $n)
+    };
+}
+
+/// Constructs arrays like [`collect_array!`] does, from iterator of `Option`s.
+///
+/// Pattern is bound to `Some` values, `None` items are skipped.
+/// Returns `None` if iterator ends before array is filled.
+///
+/// ```
+/// # use array_fu::collect_array_flatten;
+/// let values = collect_array_flatten![[Some(1), None, Some(2), None, Some(3)]; 3];
+///
+/// assert_eq!(values, Some([1, 2, 3]));
+/// ```
+///
+/// ```
+/// # use array_fu::collect_array_flatten;
+/// let values = collect_array_flatten![x in ["1", "x", "3"].map(|s| s.parse::<i32>().ok()) => x * 2; 3];
+///
+/// assert_eq!(values, None);
+/// ```
+#[macro_export]
+macro_rules! collect_array_flatten {
+    ($it:expr; $n:expr) => {
+        $crate::collect_array_flatten!(e in $it => e ; $n)
+    };
+
+    ($e:expr; $p:pat in $i:expr $(; where $($cond:expr),+ )? ; $n:expr) => {
+        $crate::collect_array_impl!(@option [] [] $e; Some($p) in $i $( ; where $($cond),+ )? ; $n)
+    };
+
+    ($p:pat in $i:expr => $e:expr $(; where $($cond:expr),+ )? ; $n:expr) => {
+        $crate::collect_array_flatten!($e; $p in $i $( ; where $($cond),+ )? ; $n)
+    };
+}
+
+#[doc(hidden)]
+#[macro_export]
+macro_rules! collect_array_impl {
+    // Splits flat tuple pattern into comma-separated elements.
+    (@flat [$($g:tt)*] [$($c:tt)*] [, $($p:tt)*] $iters:tt $($rest:tt)+) => {
+        $crate::collect_array_impl!(@flat [$($g)* [$($c)*]] [] [$($p)*] $iters $($rest)+)
+    };
+
+    (@flat [$($g:tt)*] [$($c:tt)*] [$t:tt $($p:tt)*] $iters:tt $($rest:tt)+) => {
+        $crate::collect_array_impl!(@flat [$($g)*] [$($c)* $t] [$($p)*] $iters $($rest)+)
+    };
+
+    (@flat [$($g:tt)*] [] [] $iters:tt $($rest:tt)+) => {
+        $crate::collect_array_impl!(@pair [] [$($g)*] $iters $($rest)+)
+    };
+
+    (@flat [$($g:tt)*] [$($c:tt)+] [] $iters:tt $($rest:tt)+) => {
+        $crate::collect_array_impl!(@pair [] [$($g)* [$($c)+]] $iters $($rest)+)
+    };
+
+    // Pairs pattern elements with iterators front-to-back.
+    (@pair [$([[$($p:tt)+] [$i:expr]])+] [] [] $($rest:tt)+) => {
+        $crate::collect_array!($($($p)+ in $i),+ $($rest)+)
+    };
+
+    (@pair $done:tt [[..] $($g:tt)*] [$($i:tt)*] $($rest:tt)+) => {
+        $crate::collect_array_impl!(@skip $done [$($g)*] [$($i)*] [$($g)*] [$($i)*] $($rest)+)
+    };
+
+    (@pair [$($done:tt)*] [[$($p:tt)+] $($g:tt)*] [[$i:expr] $($is:tt)*] $($rest:tt)+) => {
+        $crate::collect_array_impl!(@pair [$($done)* [[$($p)+] [$i]]] [$($g)*] [$($is)*] $($rest)+)
+    };
+
+    (@pair $done:tt $g:tt $i:tt $($rest:tt)+) => {
+        compile_error!("Number of pattern elements must match number of iterators")
+    };
+
+    // Matches iterators skipped by rest pattern with `_`,
+    // until as many iterators left as there are pattern elements after the rest pattern.
+    (@skip $done:tt $g:tt $i:tt [$g0:tt $($gc:tt)*] [$i0:tt $($ic:tt)*] $($rest:tt)+) => {
+        $crate::collect_array_impl!(@skip $done $g $i [$($gc)*] [$($ic)*] $($rest)+)
+    };
+
+    (@skip [$($done:tt)*] [$($g:tt)*] [[$i:expr] $($is:tt)*] [] [$i0:tt $($ic:tt)*] $($rest:tt)+) => {
+        $crate::collect_array_impl!(@skip [$($done)* [[_] [$i]]] [$($g)*] [$($is)*] [$($g)*] [$($is)*] $($rest)+)
+    };
+
+    (@skip $done:tt $g:tt $i:tt [] [] $($rest:tt)+) => {
+        $crate::collect_array_impl!(@pair $done $g $i $($rest)+)
+    };
+
+    (@skip $done:tt $g:tt $i:tt $gc:tt [] $($rest:tt)+) => {
+        compile_error!("Number of pattern elements must not exceed number of iterators")
+    };
+
+    // Moves pattern guards into predicates.
+    // Failed guard skips values just like failed predicate does.
+    (@guard [$($acc:tt)*] [$($g:tt)*] if $($rest:tt)+) => {
+        $crate::collect_array_impl!(@guard_if [$($acc)*] [$($g)*] [] $($rest)+)
+    };
+
+    (@guard [$($acc:tt)*] [$($g:tt)+] => $e:expr ; where $($cond:expr),+ ; $($rest:tt)+) => {
+        $crate::collect_array!($($acc)* => $e ; where $($g,)+ $($cond),+ ; $($rest)+)
+    };
+
+    (@guard [$($acc:tt)*] [$($g:tt)+] => $e:expr ; $($rest:tt)+) => {
+        $crate::collect_array!($($acc)* => $e ; where $($g),+ ; $($rest)+)
+    };
+
+    (@guard [$($acc:tt)*] [$($g:tt)*] $t:tt $($rest:tt)+) => {
+        $crate::collect_array_impl!(@guard [$($acc)* $t] [$($g)*] $($rest)+)
+    };
+
+    (@guard_if [$($acc:tt)*] [$($g:tt)*] [$($c:tt)*] in $($rest:tt)+) => {
+        $crate::collect_array_impl!(@guard [$($acc)* in] [$($g)* ($($c)*)] $($rest)+)
+    };
+
+    (@guard_if [$($acc:tt)*] [$($g:tt)*] [$($c:tt)*] $t:tt $($rest:tt)+) => {
+        $crate::collect_array_impl!(@guard_if [$($acc)*] [$($g)*] [$($c)* $t] $($rest)+)
+    };
+
+    (@$mode:ident [$($fill:tt)*] [$($idx:ident)?] $(@$batch:ident)? $e:expr; $ph:pat in $ih:expr $( , $pt:pat in $it:expr )* $(; where $($cond:expr),+ )? $(; @retry $l:lifetime)? ; $n:expr) => {{
+        #[allow(unused_mut)]
+        let mut array = $crate::builder::PartiallyInitArray::<_, $n>::uninit();
+
+        let iter = $crate::IntoIterator::into_iter($ih);
+        $( let iter = iter.zip($it); )*
+        #[allow(unused_mut)]
+        let mut iter = iter;
+
+        $($l:)? loop {
+            if array.is_full() {
+                break;
+            }
+
+            match iter.next() {
+                None => break,
+                Some($crate::pattern_list!($ph, $( $pt, )*)) => {
+                    #[allow(unreachable_code)]
+                    {
+                        $(
+                            #[allow(unused_variables)]
+                            let $idx = array.len();
+                        )?
+
+                        $($(
+                            #[allow(unused_variables, clippy::diverging_sub_expression)]
+                            #[warn(unreachable_code)]
+                            let cond = $cond;
+
+                            if <bool as $crate::Not>::not(cond) { continue; }
+                        )+)?
+
+                        #[allow(unused_variables)]
+                        let elem;
+
+                        #[allow(unused_variables)]
+                        let dont_continue_in_element_expression_without_label;
+
+                        loop {
+                            #[allow(unused)]
+                            {
+                                dont_continue_in_element_expression_without_label = ();
+                            }
+
+                            #[allow(unused_variables, clippy::diverging_sub_expression)]
+                            #[warn(unreachable_code)]
+                            let value = $e;
+
+                            elem = value;
+
+                            break $crate::DontBreakFromElementExpressionWithoutLabel;
+                        };
+
+                        $crate::collect_array_impl!(@write [$($batch)?] array elem);
+                    }
+                }
+                #[allow(unreachable_patterns)]
+                _ => continue,
+            }
+        }
+
+        $crate::collect_array_impl!(@finish $mode [$($fill)*] array iter)
+    }};
+
+    (@write [] $array:ident $elem:ident) => {
+        unsafe {
+            $array.write($elem);
+        }
+    };
+
+    (@sort $array:ident) => {
+        $array.sort_unstable()
+    };
+
+    (@sort $array:ident $k:expr) => {
+        $array.sort_unstable_by_key($k)
+    };
+
+    // Writes batch elements one by one until array is full.
+    (@write [batch] $array:ident $elem:ident) => {
+        for $elem in $crate::IntoIterator::into_iter($elem) {
+            if $array.is_full() {
+                break;
+            }
+
+            unsafe {
+                $array.write($elem);
+            }
+        }
+    };
+
+    (@finish option [] $array:ident $iter:ident) => {
+        $array.try_init()
+    };
+
+    (@finish result [] $array:ident $iter:ident) => {
+        $array.try_into_array()
+    };
+
+    (@finish partial [] $array:ident $iter:ident) => {{
+        let full = $array.is_full();
+        ($array, full)
+    }};
+
+    (@finish exact [] $array:ident $iter:ident) => {
+        match $array.is_full() && $iter.next().is_some() {
+            true => None,
+            false => $array.try_init(),
+        }
+    };
+
+    (@finish rest [] $array:ident $iter:ident) => {
+        match $array.try_init() {
+            Some(array) => Some((array, $iter)),
+            None => None,
+        }
+    };
+
+    (@finish option [$i:pat => $f:expr] $array:ident $iter:ident) => {{
+        while !$array.is_full() {
+            #[allow(unreachable_code)]
+            {
+                let $i = $array.len();
+
+                #[allow(unused_variables, clippy::diverging_sub_expression)]
+                #[warn(unreachable_code)]
+                let value = $f;
+
+                unsafe {
+                    // SAFETY: Not fully initialized.
+                    $array.write(value);
+                }
+            }
+        }
+
+        unsafe {
+            // SAFETY: `is_full` returned true.
+            $array.assume_init()
+        }
+    }};
+}
+
+/// Counter of dropped [`Counted`] values.
+#[cfg(test)]
+trait DropCounter {
+    fn increment(&self);
+}
+
+#[cfg(test)]
+impl DropCounter for std::rc::Rc<core::cell::Cell<u32>> {
+    fn increment(&self) {
+        self.set(self.get() + 1);
+    }
+}
+
+#[cfg(test)]
+impl DropCounter for std::sync::Arc<core::sync::atomic::AtomicU32> {
+    fn increment(&self) {
+        self.fetch_add(1, core::sync::atomic::Ordering::Relaxed);
+    }
+}
+
+/// Tagged value that increments shared counter when dropped.
+/// Values are compared by tag.
+#[cfg(test)]
+struct Counted<T = (), C: DropCounter = std::rc::Rc<core::cell::Cell<u32>>>(T, C);
+
+#[cfg(test)]
+impl<C: DropCounter + Clone> Counted<(), C> {
+    fn new(drops: &C) -> Self {
+        Counted((), drops.clone())
+    }
+}
+
+#[cfg(test)]
+impl<T: PartialEq, C: DropCounter> PartialEq for Counted<T, C> {
+    fn eq(&self, other: &Self) -> bool {
+        self.0 == other.0
+    }
+}
+
+#[cfg(test)]
+impl<T, C: DropCounter> Drop for Counted<T, C> {
+    fn drop(&mut self) {
+        self.1.increment();
+    }
+}
+
+#[test]
+fn test_expression_repeat() {
+    let mut i = 0;
+    assert_eq!(array!({ i+=1; i }; 2), [1, 2]);
+}
+
+#[test]
+fn test_comprehension_repeat() {
+    assert_eq!(array!(x => x * 2; 3), [0, 2, 4]);
+    assert_eq!(array!(x => x * 2; where x & 1 == 1; 3), [2, 6, 10]);
+}
+
+#[test]
+fn test_comprehension_iter() {
+    assert_eq!(
+        collect_array!(x * 2; x in 1..3; 3),
+        None,
+        "There's not enough elements in iterator"
+    );
+    assert_eq!(
+        collect_array!(x * 2; x in 1..; 3),
+        Some([2, 4, 6]),
+        "1*2, 2*2, 3*2"
+    );
+    assert_eq!(
+        collect_array!(x * y; x in 1.., y in (1..3).cycle(); where x > 3, y == 1; 3),
+        Some([5, 7, 9]),
+        "x = 1,2,3,4,5,6,7,8,9
+         y = 1,2,1,2,1,2,1,2,1
+         r = x,x,x,x,5,x,7,x,9"
+    );
+
+    assert_eq!(
+        collect_array!(x in 0.. => x * 2; where x & 1 == 1; 3),
+        Some(array!(x => x * 2; where x & 1 == 1; 3)),
+    );
+
+    assert_eq!(
+        collect_array!(x in 0.., _y in 1.., _z in 2.., _w in 3..5 => x; where x & 1 == 1; 3),
+        None,
+    );
+}
+
+#[test]
+fn test_bail() {
+    array!(return; 2);
+    panic!();
+}
+
+#[test]
+fn test_bail_condition() {
+    array!(_ => 0; where return; 1);
+    panic!();
+}
+
+#[test]
+fn test_bail_iter() {
+    collect_array!(_ in 1.. => 0; where return; 1);
+    panic!();
+}
+
+#[test]
+#[should_panic]
+fn test_bail_panic() {
+    array!(return; 0);
+    panic!();
+}
+
+#[test]
+#[should_panic]
+fn test_bail_condition_panic() {
+    array!(_ => 0; where return; 0);
+    panic!();
+}
+
+#[test]
+fn test_initialized_slice() {
+    let mut array = PartiallyInitArray::<u32, 5>::uninit();
+    assert_eq!(array.as_initialized_slice(), &[]);
+    assert!(array.is_empty());
+
+    unsafe {
+        array.write(1);
+        array.write(1);
+    }
+
+    while !array.is_full() {
+        let next = match array.as_initialized_slice() {
+            [.., a, b] => a + b,
+            _ => unreachable!(),
+        };
+        unsafe { array.write(next) }
+    }
+    assert_eq!(array.as_initialized_slice(), &[1, 1, 2, 3, 5]);
+
+    array.as_initialized_slice_mut()[0] = 0;
+    assert_eq!(array.try_init(), Some([0, 1, 2, 3, 5]));
+}
+
+#[test]
+#[allow(deprecated)]
+fn test_init_slice() {
+    let mut array = PartiallyInitArray::<u32, 3>::uninit();
+    assert_eq!(array.as_init_slice(), &[]);
+
+    let _ = array.push(1);
+    let _ = array.push(2);
+    array.as_init_mut_slice()[0] = 5;
+    assert_eq!(array.as_init_slice(), &[5, 2]);
+    assert!(array.as_init_slice().contains(&2));
+}
+
+#[test]
+fn test_try_array() {
+    assert_eq!(try_array!(x => x * 2; 3), Some([0, 2, 4]));
+    assert_eq!(try_array!(1 => 1; 0), Some([]));
+    assert_eq!(try_array!(1..=2 => 7; 0), Some([]));
+
+    let values: Option<[u8; 3]> = try_array!(x @ 1..=2 => x; 3);
+    assert_eq!(values, None, "Only two values match the pattern");
+
+    let values: Option<[u8; 1]> = try_array!(x => x; where false; 1);
+    assert_eq!(values, None);
+}
+
+#[test]
+fn test_try_array_infeasible() {
+    use std::{cell::Cell, rc::Rc};
+
+    let drops = Rc::new(Cell::new(0));
+
+    // Only 0, 100 and 200 pass the predicate before `u8` enumerator wraps.
+    let values = try_array![x: u8 => Counted::new(&drops); where x % 100 == 0; 5];
+    assert!(values.is_none());
+    assert_eq!(drops.get(), 3);
+
+    let values = try_array![x: u8 => x; where x % 100 == 0; 3];
+    assert_eq!(values, Some([0, 100, 200]));
+}
+
+#[test]
+fn test_try_array_bail() {
+    fn bail() -> Option<[u8; 1]> {
+        try_array!(return None; 1);
+        unreachable!()
+    }
+    assert_eq!(bail(), None);
+
+    let values = 'a: loop {
+        let _: Option<[u8; 1]> = try_array!(break 'a 42; 1);
+    };
+    assert_eq!(values, 42);
+}
+
+#[test]
+fn test_enumerate_from() {
+    let mut calls = 0;
+    let values = array![x from { calls += 1; 10 } => x * 2; where x % 3 != 0; 3];
+    assert_eq!(values, [20, 22, 26]);
+    assert_eq!(calls, 1);
+
+    let values: Option<[u8; 1]> = try_array!(x from 200u8 => x; where x == 199; 1);
+    assert_eq!(values, Some([199]), "Enumeration wraps around to reach 199");
+
+    let values: Option<[u8; 1]> = try_array!(x from 200u8 => x; where false; 1);
+    assert_eq!(values, None);
+
+    assert_eq!(array!(x from 10 => x * 2; 4), [20, 22, 24, 26]);
+    assert_eq!(array!(x from 10 => x; where x >= 12; 2), [12, 13]);
+    assert_eq!(array!(x from 1 by 2 => x; 3), [1, 3, 5]);
+    assert_eq!(array!(x by 2 from 1 => x; 3), [1, 3, 5]);
+    assert_eq!(array!(x: u8 from 255 => x; 2), [255, 0]);
+}
+
+#[test]
+#[should_panic(expected = "whole 'u8' space")]
+fn test_enumerate_from_panic() {
+    array!(x from 250u8 => x; where false; 1);
+}
+
+#[test]
+fn test_len() {
+    let mut array = PartiallyInitArray::<u32, 3>::uninit();
+    assert_eq!((array.len(), array.remaining()), (0, 3));
+
+    while array.remaining() > 0 {
+        unsafe { array.write(array.len() as u32) }
+    }
+    assert!(!array.is_empty());
+    assert_eq!((array.len(), array.remaining()), (3, 0));
+    assert_eq!(array.try_init(), Some([0, 1, 2]));
+}
+
+#[test]
+fn test_tries() {
+    assert_eq!(
+        try_array!(x => x; where x % 2 == 0, x % 3 == 0; tries 13; 3),
+        Some([0, 6, 12])
+    );
+    assert_eq!(
+        try_array!(x => x; where x % 2 == 0, x % 3 == 0; tries 12; 3),
+        None
+    );
+    assert_eq!(try_array!(x => x; tries 0; 0), Some([]));
+    assert_eq!(array!(x => x; where x > 0; tries 0; 0), []);
+}
+
+#[test]
+#[should_panic(expected = "1 of 2 elements initialized")]
+fn test_tries_panic() {
+    array!(x => x; where x % 10 == 0; tries 10; 2);
+}
+
+#[test]
+#[should_panic(expected = "step must be non-zero")]
+fn test_enumerate_by_zero() {
+    array!(x by 0 => x; 4);
+}
+
+#[test]
+fn test_enumerate_by() {
+    assert_eq!(array!(x by 2 => x; where x % 3 != 0; 4), [2, 4, 8, 10]);
+    assert_eq!(array!(x by 5 from 3 => x; 3), [3, 8, 13]);
+    assert_eq!(array!(x from 1 by 2 => x; 4), [1, 3, 5, 7]);
+    assert_eq!(array!(x by 3 => x; where x % 2 == 1; 3), [3, 9, 15]);
+
+    let values: Option<[u8; 1]> = try_array!(x by 2u8 => x; where x & 1 == 1; 1);
+    assert_eq!(values, None, "Only even values are enumerated");
+
+    let values: Option<[u8; 1]> = try_array!(x from 1 by 2 => x; where x == 255; 1);
+    assert_eq!(values, Some([255]));
+}
+
+#[test]
+fn test_push() {
+    let mut array = builder::PartiallyInitArray::<u32, 2>::uninit();
+    assert!(!array.is_full());
+    assert_eq!(array.push(1), Ok(()));
+    assert_eq!((array.len(), array.remaining()), (1, 1));
+    assert_eq!(array.push(2), Ok(()));
+    assert!(array.is_full());
+    assert_eq!(array.push(3), Err(3));
+    assert_eq!(array.try_init(), Some([1, 2]));
+}
+
+#[test]
+fn test_pop() {
+    let mut array = PartiallyInitArray::<u32, 3>::uninit();
+    assert_eq!(array.pop(), None);
+
+    let _ = array.push(1);
+    let _ = array.push(2);
+    assert_eq!(array.pop(), Some(2));
+    assert_eq!(array.len(), 1);
+
+    let _ = array.push(3);
+    let _ = array.push(4);
+    assert_eq!(array.try_init(), Some([1, 3, 4]));
+
+    let value = std::rc::Rc::new(());
+    let mut array = PartiallyInitArray::<_, 2>::uninit();
+    let _ = array.push(value.clone());
+    let _ = array.push(value.clone());
+    let popped = array.pop();
+    drop(array);
+    assert_eq!(
+        std::rc::Rc::strong_count(&value),
+        2,
+        "Popped value is not dropped by array"
+    );
+    drop(popped);
+    assert_eq!(std::rc::Rc::strong_count(&value), 1);
+}
+
+#[test]
+#[allow(deprecated)]
+fn test_try_write() {
+    let mut array = PartiallyInitArray::<u32, 2>::uninit();
+    assert_eq!(array.try_write(1), Ok(()));
+    assert_eq!(array.try_write(2), Ok(()));
+    assert!(array.is_init());
+    assert_eq!(array.try_write(3), Err(3));
+    assert_eq!(array.try_init(), Some([1, 2]));
+}
+
+#[test]
+fn test_enumerate_down_from() {
+    assert_eq!(array!(x down_from 10 => x; where x % 3 != 0; 3), [10, 8, 7]);
+    assert_eq!(array!(x down_from 10 by 4 => x; 3), [10, 6, 2]);
+    assert_eq!(array!(x down_from 1i8 => x; 3), [1, 0, -1]);
+
+    let values: Option<[u8; 2]> = try_array!(x down_from 1 => x; where x != 0; 2);
+    assert_eq!(
+        values,
+        Some([1, 255]),
+        "Enumeration wraps around below zero"
+    );
+
+    let values: Option<[u8; 1]> = try_array!(x down_from 1 => x; where false; 1);
+    assert_eq!(values, None);
+}
+
+#[test]
+fn test_extend_from_slice() {
+    let mut array = PartiallyInitArray::<u32, 5>::uninit();
+    assert_eq!(array.extend_from_slice(&[1, 2]), 2);
+    assert_eq!(array.extend_from_slice(&[]), 0);
+    assert_eq!(array.extend_from_slice(&[3, 4, 5, 6]), 3);
+    assert_eq!(array.extend_from_slice(&[7]), 0);
+    assert_eq!(array.try_init(), Some([1, 2, 3, 4, 5]));
+}
+
+#[test]
+fn test_extend_from_iter() {
+    let mut array = PartiallyInitArray::<u32, 5>::uninit();
+    assert_eq!(array.extend_from_iter([1, 2]), 2);
+    assert_eq!(array.extend_from_iter(core::iter::empty()), 0);
+
+    let mut iter = 10..;
+    assert_eq!(array.extend_from_iter(&mut iter), 3);
+    assert_eq!(
+        iter.next(),
+        Some(13),
+        "Iterator is not advanced past the last written value"
+    );
+    assert_eq!(array.extend_from_iter(&mut iter), 0);
+    assert_eq!(iter.next(), Some(14));
+    assert_eq!(array.try_init(), Some([1, 2, 10, 11, 12]));
+}
+
+#[test]
+fn test_extend() {
+    let mut array = PartiallyInitArray::<u32, 3>::uninit();
+    array.extend([1]);
+
+    let mut iter = 2..;
+    array.extend(&mut iter);
+    assert_eq!(iter.next(), Some(4));
+    array.extend([5, 6]);
+    assert_eq!(array.try_init(), Some([1, 2, 3]));
+
+    assert_eq!(
+        PartiallyInitArray::<u32, 2>::from_iter_checked(1..),
+        Some([1, 2])
+    );
+    assert_eq!(PartiallyInitArray::<u32, 2>::from_iter_checked([1]), None);
+}
+
+#[test]
+fn test_enumerate_typed() {
+    let values: [u64; 3] = array![x: u8 => u64::from(x) * 1000; 3];
+    assert_eq!(values, [0, 1000, 2000]);
+
+    assert_eq!(
+        try_array!(x: u8 from 250 by 3 => x; where x < 10; 2),
+        Some([0, 3])
+    );
+    assert_eq!(try_array!(_: u8 => 0; where false; 1), None);
+
+    let mut attempts = 0;
+    let values = try_array!(_: u8 => 0; where { attempts += 1; false }; 1);
+    assert_eq!(values, None);
+    assert_eq!(attempts, 256);
+}
+
+#[test]
+#[should_panic(expected = "whole 'u16' space")]
+fn test_enumerate_typed_panic() {
+    array!(_: u16 => 0; where false; 1);
+}
+
+#[test]
+fn test_modifier_keywords_as_names() {
+    assert_eq!(array![from => from * 2; 3], [0, 2, 4]);
+    assert_eq!(array![by => by + 1; 3], [1, 2, 3]);
+    assert_eq!(array![rev => rev; 3], [0, 1, 2]);
+    assert_eq!(array![down_from => down_from; 2], [0, 1]);
+    assert_eq!(array![over => over; 2], [0, 1]);
+    assert_eq!(array![step => step * 2; 3], [0, 2, 4]);
+    assert_eq!(array![starting => starting; 2], [0, 1]);
+
+    let by = 2;
+    let from = 1;
+    assert_eq!(array![x from from by by => x; 3], [1, 3, 5]);
+    assert_eq!(array![x rev from from => x; 3], [3, 2, 1]);
+}
+
+#[test]
+fn test_step_clause() {
+    assert_eq!(array!(x => x; where x % 2 == 0; step 3; 3), [0, 6, 12]);
+    assert_eq!(array!(x from 1 => x; step 3; where x > 5; 2), [7, 10]);
+    assert_eq!(array!(x: i8 => x; step -50; where x < 0; 2), [-50, -100]);
+
+    let values: Option<[u8; 1]> = try_array!(x => x; step 4; where x % 4 != 0; 1);
+    assert_eq!(values, None);
+}
+
+#[test]
+fn test_rev() {
+    let mut values = array!(x => x * 3; 5);
+    values.reverse();
+    assert_eq!(array!(x => x * 3; rev; 5), values);
+
+    let mut values = array!(x down_from 10 by 2 => x; 3);
+    values.reverse();
+    assert_eq!(array!(x down_from 10 by 2 => x; rev; 3), values);
+
+    assert_eq!(array!(x => x; rev; where x % 2 == 0; 3), [2, 0, -2]);
+    assert_eq!(array!(x => x; rev; 0), [0; 0]);
+
+    let values: Option<[u8; 2]> = try_array!(x => x; rev; where x != 1; 2);
+    assert_eq!(values, Some([0, 255]));
+
+    let values: Option<[u8; 2]> = try_array!(x => x; rev; where false; 2);
+    assert_eq!(values, None);
+}
+
+#[test]
+fn test_into_iter() {
+    let mut array = PartiallyInitArray::<u32, 5>::uninit();
+    array.extend_from_slice(&[1, 2, 3]);
+
+    let mut iter = array.into_iter();
+    assert_eq!(iter.len(), 3);
+    assert_eq!(iter.next(), Some(1));
+    assert_eq!(iter.len(), 2);
+    assert_eq!(iter.next(), Some(2));
+    assert_eq!(iter.next(), Some(3));
+    assert_eq!(iter.next(), None);
+    assert_eq!(iter.next(), None);
+
+    let drops = std::rc::Rc::new(core::cell::Cell::new(0));
+    let mut array = PartiallyInitArray::<Counted, 5>::uninit();
+    for _ in 0..3 {
+        let _ = array.push(Counted::new(&drops));
+    }
+
+    let mut iter = array.into_iter();
+    drop(iter.next());
+    assert_eq!(drops.get(), 1);
+    drop(iter);
+    assert_eq!(drops.get(), 3);
+}
+
+#[test]
+fn test_into_iter_panic() {
+    use std::{panic, rc::Rc};
+
+    let value = Rc::new(());
+    let mut array = PartiallyInitArray::<Rc<()>, 5>::uninit();
+    array.extend(core::iter::repeat_with(|| value.clone()).take(4));
+
+    let result = panic::catch_unwind(panic::AssertUnwindSafe(|| {
+        for (i, item) in array.into_iter().enumerate() {
+            assert!(i < 2, "boom");
+            drop(item);
+        }
+    }));
+    assert!(result.is_err());
+    assert_eq!(
+        Rc::strong_count(&value),
+        1,
+        "Unread elements are dropped on unwind"
+    );
+}
+
+#[test]
+fn test_try_collect_array() {
+    assert_eq!(try_collect_array!(x * 2; x in 1..; 3).ok(), Some([2, 4, 6]));
+
+    let partial = try_collect_array!(x in 1..10, y in 1..4 => x * y; where x != 2; 3).unwrap_err();
+    assert_eq!(partial.len(), 2);
+    assert_eq!(partial.as_initialized_slice(), &[1, 9]);
+}
+
+#[test]
+fn test_collect_array_or_default() {
+    assert_eq!(collect_array_or_default!(1..; 3), [1, 2, 3]);
+    assert_eq!(collect_array_or_default!(x * 2; x in 1..3; 3), [2, 4, 0]);
+    assert_eq!(
+        collect_array_or_default!((x, y) in [(1, 2), (3, 4)] => x + y; 0),
+        [0; 0]
+    );
+}
+
+#[test]
+fn test_debug() {
+    let mut array = PartiallyInitArray::<u32, 5>::uninit();
+    array.extend_from_slice(&[1, 2]);
+
+    assert_eq!(
+        std::format!("{:?}", array),
+        "PartiallyInitArray { initialized: [1, 2], remaining: 3 }"
+    );
+}
+
+#[test]
+fn test_clone() {
+    let mut array = PartiallyInitArray::<std::string::String, 3>::uninit();
+    let _ = array.push("a".into());
+    let _ = array.push("b".into());
+
+    let mut checkpoint = array.clone();
+    array.as_initialized_slice_mut()[0].push('!');
+    let _ = array.push("c".into());
+
+    assert_eq!(array.as_initialized_slice(), ["a!", "b", "c"]);
+    assert_eq!(checkpoint.as_initialized_slice(), ["a", "b"]);
+    assert_eq!(checkpoint.remaining(), 1);
+
+    let _ = checkpoint.push("d".into());
+    assert_eq!(
+        checkpoint.try_init(),
+        Some(["a".into(), "b".into(), "d".into()])
+    );
+}
+
+#[test]
+fn test_collect_array_else() {
+    assert_eq!(collect_array!(1..; else |_| 0; 3), [1, 2, 3]);
+    assert_eq!(
+        collect_array!(x in 1..10 => x; where x % 4 == 0; else |i| i * 100; 4),
+        [4, 8, 200, 300]
+    );
+    assert_eq!(
+        collect_array!(x * y; x in 1..3, y in 1..; else |i| i; 3),
+        [1, 4, 2]
+    );
+    assert_eq!(collect_array!(1..; else |_| unreachable!(); 2), [1, 2]);
+}
+
+#[test]
+fn test_collect_array_or() {
+    assert_eq!(collect_array!([1, 2]; or 0; 4), [1, 2, 0, 0]);
+    assert_eq!(collect_array!(x in [1, 2] => x * 2; or 0; 4), [2, 4, 0, 0]);
+    assert_eq!(
+        collect_array!(x in 1..; i => x + i; where x > 2; or 0; 2),
+        [3, 5]
+    );
+    assert_eq!(
+        collect_array!(x if x % 2 == 0 in 0..5 => x; or 9; 4),
+        [0, 2, 4, 9]
+    );
+
+    let mut calls = 0;
+    let values = collect_array!(x in ["a"] => std::string::String::from(x); or { calls += 1; std::string::String::new() }; 3);
+    assert_eq!(values, ["a", "", ""]);
+    assert_eq!(calls, 2);
+}
+
+#[test]
+fn test_rev_modifier() {
+    assert_eq!(array!(x rev => x; 5), [4, 3, 2, 1, 0]);
+    assert_eq!(array!(x from 1 by 2 rev => x; 3), [5, 3, 1]);
+    assert_eq!(array!(x rev from 1 => x; 3), [3, 2, 1]);
+    assert_eq!(
+        array!(x rev => x; where x % 2 == 0; 3),
+        [2, 0, u32::MAX - 1]
+    );
+}
+
+#[test]
+fn test_array_else() {
+    let mut fallback = 100;
+    let values = array!(x => x; where x % 5 == 0; tries 15; else { fallback += 1; fallback }; 8);
+    assert_eq!(values, [0, 5, 10, 101, 102, 103, 104, 105]);
+
+    let values: [u8; 3] = array!(x => x; where x == 7; else 0; 3);
+    assert_eq!(values, [7, 0, 0]);
+
+    assert_eq!(array!(x => x; else unreachable!(); 3), [0, 1, 2]);
+}
+
+#[test]
+fn test_fill_with() {
+    let mut array = PartiallyInitArray::<u32, 4>::uninit();
+    array.extend_from_slice(&[1]);
+    let mut next = 10;
+    array.fill_with(|| {
+        next += 1;
+        next
+    });
+    array.fill_with(|| unreachable!());
+    assert_eq!(array.try_init(), Some([1, 11, 12, 13]));
+
+    let mut array = PartiallyInitArray::<u32, 4>::uninit();
+    let mut next = 0;
+    let result = array.fill_with_result(|| {
+        next += 1;
+        if next < 3 {
+            Ok(next)
+        } else {
+            Err(next)
+        }
+    });
+    assert_eq!(result, Err(3));
+    assert_eq!(array.as_initialized_slice(), &[1, 2]);
+    assert_eq!(array.fill_with_result(|| Ok::<_, ()>(7)), Ok(()));
+    assert_eq!(array.try_init(), Some([1, 2, 7, 7]));
+}
+
+#[test]
+fn test_collect_array_rest() {
+    let (array, rest) = collect_array_rest!(x in 1..10 => x; where x % 2 == 0; 2).unwrap();
+    assert_eq!(array, [2, 4]);
+    assert!(rest.eq(5..10));
+
+    let (array, mut rest) = collect_array_rest!(x + y; x in 1.., y in 10..; 2).unwrap();
+    assert_eq!(array, [11, 13]);
+    assert_eq!(rest.next(), Some((3, 12)));
+
+    assert!(collect_array_rest!(1..3; 3).is_none());
+}
+
+#[test]
+fn test_state() {
+    use std::{cell::Cell, rc::Rc};
+
+    let drops = Rc::new(Cell::new(0));
+    let values =
+        array!(state c = Counted(0, drops.clone()); x => { c.0 += x; c.0 }; where c.0 < 10; 4);
+    assert_eq!(values, [0, 1, 3, 6]);
+    assert_eq!(drops.get(), 1);
+
+    let values = array!(state seen = std::vec::Vec::new(); x => { seen.push(x); seen.len() }; where x % 2 == 1; 3);
+    assert_eq!(values, [1, 2, 3]);
+
+    let drops = Rc::new(Cell::new(0));
+    let result = std::panic::catch_unwind(std::panic::AssertUnwindSafe(|| {
+        array!(state _c = Counted::new(&drops); x => {
+            if x == 2 { panic!() }
+            Counted(x, drops.clone())
+        }; 4)
+    }));
+    assert!(result.is_err());
+    assert_eq!(drops.get(), 3, "State and two elements are dropped");
+}
+
+#[test]
+fn test_try_init_or_fill() {
+    let mut array = PartiallyInitArray::<u32, 4>::uninit();
+    array.extend_from_slice(&[1, 2]);
+    assert_eq!(array.try_init_or_fill(|| 0), [1, 2, 0, 0]);
+
+    let mut array = PartiallyInitArray::<u32, 2>::uninit();
+    array.extend_from_slice(&[1, 2]);
+    assert_eq!(array.try_init_or_fill(|| unreachable!()), [1, 2]);
+}
+
+#[test]
+fn test_built_prefix() {
+    let values = array!(x, built => built.last().copied().unwrap_or(0) + x; 5);
+    assert_eq!(values, [0, 1, 3, 6, 10]);
+
+    let values = array!(x from 1, built => x / 3; where !built.contains(&(x / 3)); 3);
+    assert_eq!(values, [0, 1, 2]);
+
+    let values = array!(x: u8, built => x % 5; where !built.contains(&(x % 5)); tries 6; else 9; 6);
+    assert_eq!(values, [0, 1, 2, 3, 4, 9]);
+}
+
+#[test]
+fn test_collect_array_result() {
+    let items = [Ok(1), Ok(2), Err("bad"), Ok(4)];
+    assert_eq!(collect_array_result!(items; 2), Ok(Some([1, 2])));
+    assert_eq!(collect_array_result!(items; 3), Err("bad"));
+    assert_eq!(
+        collect_array_result!(x in items => x; where x > 1; 2),
+        Err("bad")
+    );
+    assert_eq!(
+        collect_array_result!(x in items[..2].iter().copied() => x; 3),
+        Ok(None)
+    );
+
+    let mut iter = items.iter().copied();
+    assert_eq!(collect_array_result!(x in &mut iter => x; 1), Ok(Some([1])));
+    assert_eq!(iter.next(), Some(Ok(2)), "No extra items are consumed");
+}
+
+#[test]
+fn test_collect_array_result_else() {
+    let ok: Result<[i32; 3], &str> =
+        collect_array_result!(x in [Ok(1), Ok(2)] => x; else |i| i as i32 * 10; 3);
+    assert_eq!(ok, Ok([1, 2, 20]));
+
+    let err: Result<[i32; 3], &str> =
+        collect_array_result!(x in [Ok(1), Err("bad"), Ok(3)] => x; where x > 0; else |_| 0; 3);
+    assert_eq!(err, Err("bad"));
+}
+
+#[test]
+fn test_reset() {
+    let value = std::rc::Rc::new(());
+    let mut array = PartiallyInitArray::<_, 3>::uninit();
+    array.fill_with(|| value.clone());
+    assert_eq!(std::rc::Rc::strong_count(&value), 4);
+
+    array.reset();
+    assert!(array.is_empty());
+    assert_eq!(std::rc::Rc::strong_count(&value), 1);
+
+    let _ = array.push(value.clone());
+    assert_eq!(array.len(), 1);
+    drop(array);
+    assert_eq!(std::rc::Rc::strong_count(&value), 1);
+}
+
+#[test]
+fn test_post_condition() {
+    assert_eq!(
+        array!(x => x * 7 % 10; where after |&e| e > 4; 3),
+        [7, 8, 5]
+    );
+    assert_eq!(
+        array!(x => x; where x % 2 == 0; where after |e| e % 3 == 0; 3),
+        [0, 6, 12]
+    );
+
+    let values: Option<[u8; 1]> = try_array!(x => x; where after |_| false; 1);
+    assert_eq!(values, None);
+}
+
+#[test]
+fn test_post_condition_drops_rejected() {
+    use std::{cell::Cell, rc::Rc};
+
+    let drops = Rc::new(Cell::new(0));
+
+    let values = array![x: u32 => Counted(x, drops.clone()); where after |e| e.0 % 3 == 0; 3];
+    assert_eq!(drops.get(), 4);
+    assert_eq!(
+        values.iter().map(|e| e.0).collect::<std::vec::Vec<_>>(),
+        [0, 3, 6]
+    );
+
+    drop(values);
+    assert_eq!(drops.get(), 7);
+}
+
+#[test]
+fn test_collect_array_flatten() {
+    assert_eq!(
+        collect_array_flatten!([None, Some(1), None, Some(2), Some(3), Some(4)]; 3),
+        Some([1, 2, 3])
+    );
+    assert_eq!(
+        collect_array_flatten!(x in (0..).map(|x| (x % 3 == 0).then_some(x)) => x + 1; where x > 0; 2),
+        Some([4, 7])
+    );
+    assert_eq!(collect_array_flatten!([Some(1), None]; 2), None);
+}
+
+#[test]
+fn test_collect_array_exact() {
+    assert_eq!(collect_array_exact!([1, 2, 3]; 3), Some([1, 2, 3]));
+    assert_eq!(collect_array_exact!([1, 2, 3, 4]; 3), None);
+    assert_eq!(collect_array_exact!([1, 2]; 3), None);
+    assert_eq!(
+        collect_array_exact!(x in 0..5 => x; where x % 2 == 0; 3),
+        Some([0, 2, 4])
+    );
+    assert_eq!(
+        collect_array_exact!(x in 0..7 => x; where x % 2 == 0; 3),
+        None
+    );
+    assert_eq!(
+        collect_array_exact!(x in 0.., y in 0..2 => x + y; 2),
+        Some([0, 2])
+    );
+}
+
+#[test]
+fn test_distinct() {
+    assert_eq!(array!(x => x * x % 7; distinct; 4), [0, 1, 4, 2]);
+    assert_eq!(array!(x => x / 3; where x % 2 == 1; distinct; 3), [0, 1, 2]);
+    assert_eq!(try_array!(x => x % 3; distinct; tries 100; 4), None);
+
+    let mut seed = 17u32;
+    let values = array![_ => {
+        seed = seed.wrapping_mul(1103515245).wrapping_add(12345);
+        (seed >> 16) as u8 % 8
+    }; distinct; 8];
+    let mut sorted = values;
+    sorted.sort();
+    assert_eq!(sorted, [0, 1, 2, 3, 4, 5, 6, 7]);
+}
+
+#[test]
+#[should_panic(expected = "attempt budget, 3 of 4")]
+fn test_distinct_budget() {
+    array!(x => x % 3; distinct; tries 100; 4);
+}
+
+#[test]
+fn test_distinct_drops_rejected() {
+    use std::{cell::Cell, rc::Rc};
+
+    let drops = Rc::new(Cell::new(0));
+
+    let values = array![x: u32 => Counted(x / 2, drops.clone()); distinct; 3];
+    assert_eq!(drops.get(), 2);
+    drop(values);
+    assert_eq!(drops.get(), 5);
+}
+
+#[test]
+fn test_collect_array_index() {
+    assert_eq!(
+        collect_array!(x in 1..; i => x * 10 + i; where x % 2 == 0; 3),
+        Some([20, 41, 62])
+    );
+    assert_eq!(
+        collect_array!(x in [5, 6], y in 0..; i => x + y * i; 2),
+        Some([5, 7])
+    );
+    assert_eq!(
+        collect_array!(x in [5, 6]; i => x + i; where i < 1; 2),
+        None
+    );
+    assert_eq!(
+        collect_array!(x in [5, 6]; i => x + i; else |j| j * 10; 4),
+        [5, 7, 20, 30]
+    );
+}
+
+#[test]
+fn test_collect_array_leading_index() {
+    assert_eq!(
+        collect_array!(i, x in ["a", "b", "c"] => (i, x); 3),
+        Some([(0, "a"), (1, "b"), (2, "c")])
+    );
+    assert_eq!(
+        collect_array!(i, x in 10.., y in 0.. => x * y + i; where x % 2 == 0; 3),
+        Some([0, 25, 58])
+    );
+    assert_eq!(
+        collect_array!(i, x in [5] => x + i; else |j| j; 3),
+        [5, 1, 2]
+    );
+    assert_eq!(collect_array!(i, x in [5] => x + i; or 0; 3), [5, 0, 0]);
+    assert_eq!(collect_array!(i, x in [5] => x + i; 3), None);
+}
+
+#[test]
+fn test_let_prelude() {
+    let mut calls = 0;
+    let values = array![let base = { calls += 1; 10 }; x => base + x; where x + base > 11; 3];
+    assert_eq!(values, [12, 13, 14]);
+    assert_eq!(calls, 1);
+
+    let values = array![let names = std::vec!["a", "b"], len = names.len(); i => names[i % len].repeat(i + 1); 3];
+    assert_eq!(values, ["a", "bb", "aaa"]);
+
+    let values = array![let step: u32 = 3; state acc = 0; _ => { acc += step; acc }; 3];
+    assert_eq!(values, [3, 6, 9]);
+
+    let values: Option<[u8; 1]> = try_array![let limit = 0; x => x; where x < limit; 1];
+    assert_eq!(values, None);
+}
+
+#[test]
+fn test_array_clone() {
+    use std::{cell::Cell, rc::Rc};
+
+    struct CloneCounted(Rc<Cell<u32>>);
+
+    impl Clone for CloneCounted {
+        fn clone(&self) -> Self {
+            self.0.set(self.0.get() + 1);
+            CloneCounted(self.0.clone())
+        }
+    }
+
+    let clones = Rc::new(Cell::new(0));
+    let _: [CloneCounted; 5] = array![clone CloneCounted(clones.clone()); 5];
+    assert_eq!(clones.get(), 4);
+
+    let clones = Rc::new(Cell::new(0));
+    let _: [CloneCounted; 1] = array![clone CloneCounted(clones.clone()); 1];
+    assert_eq!(clones.get(), 0);
+
+    let clones = Rc::new(Cell::new(0));
+    let _: [CloneCounted; 0] = array![clone CloneCounted(clones.clone()); 0];
+    assert_eq!(clones.get(), 0);
+    assert_eq!(Rc::strong_count(&clones), 1);
+
+    assert_eq!(array![clone 7; 3], [7, 7, 7]);
+}
+
+#[test]
+fn test_array_clone_panic() {
+    use std::{cell::Cell, rc::Rc};
+
+    struct Bomb(Rc<Cell<u32>>);
+
+    impl Clone for Bomb {
+        fn clone(&self) -> Self {
+            if self.0.get() == 2 {
+                panic!("boom");
+            }
+            self.0.set(self.0.get() + 1);
+            Bomb(self.0.clone())
+        }
+    }
+
+    let clones = Rc::new(Cell::new(0));
+    let value = Bomb(clones.clone());
+    let result = std::panic::catch_unwind(std::panic::AssertUnwindSafe(|| {
+        let _: [Bomb; 4] = array![clone value; 4];
+    }));
+    assert!(result.is_err());
+    assert_eq!(
+        Rc::strong_count(&clones),
+        1,
+        "All clones and the original are dropped"
+    );
+}
+
+#[test]
+fn test_array_while_let() {
+    let mut stack = std::vec![1, 2, 3, 4];
+    let values = array![while let Some(v) = stack.pop() => v * 10; 4];
+    assert_eq!(values, Some([40, 30, 20, 10]));
+    assert!(stack.is_empty());
+
+    let mut stack = std::vec![1, 2, 3];
+    let values = array![while let Some(v) = stack.pop() => v; where v != 2; 3];
+    assert_eq!(values, None);
+
+    let mut n = 0;
+    let values = try_array![while let 0..=9 = { n += 3; n } => n; 3];
+    assert_eq!(values, Some([3, 6, 9]));
+    let values = try_array![while let 0..=9 = { n += 3; n } => n; 1];
+    assert_eq!(values, None);
+}
+
+#[test]
+fn test_collect_array_partial() {
+    let (values, full) = collect_array_partial!(x in 1..3 => x * 2; 3);
+    assert!(!full);
+    assert_eq!(values.as_initialized_slice(), &[2, 4]);
+
+    let (values, full) = collect_array_partial!(x in 0.., y in 0..5 => x + y; where x % 2 == 0; 2);
+    assert!(full);
+    assert_eq!(values.try_init(), Some([0, 4]));
+
+    let (values, full) = collect_array_partial!(core::iter::empty::<u8>(); 0);
+    assert!(full);
+    assert_eq!(values.try_init(), Some([]));
+}
+
+#[test]
+fn test_arms() {
+    assert_eq!(
+        array!(0 => "zero", n if n % 2 == 0 => "even", _ => "odd"; 5),
+        ["zero", "odd", "even", "odd", "even"]
+    );
+    assert_eq!(
+        array!(x if x < 2 => 1, 0..=2 => 2, _ => 3; 4),
+        [1, 1, 2, 3],
+        "First matching arm wins"
+    );
+    assert_eq!(array!(1 => 10, 3 | 4 => 30; 3), [10, 30, 30]);
+    assert_eq!(
+        array!(state n = 0; x @ 1..=2 from 1 => x, _ => 0; where { n += 1; n % 2 == 0 }; 3),
+        [2, 0, 0]
+    );
+    assert_eq!(
+        array!(x @ 0..=1, built => x + built.len(), _ => 0; 3),
+        [0, 2, 0]
+    );
+
+    let values: Option<[u8; 3]> = try_array!(1u8 => 1, 2 => 2; 3);
+    assert_eq!(values, None);
+}
+
+#[test]
+fn test_guards() {
+    assert_eq!(array!(x if x % 3 != 0 => x * x; 5), [1, 4, 16, 25, 49]);
+    assert_eq!(array!(x @ 0..=9 if x > 2 => x; where x % 2 == 0; 2), [4, 6]);
+    assert_eq!(array!(x: u8 if x != 1 => x; 2), [0, 2]);
+    assert_eq!(array!(x from 5 rev if x != 6 => x; 3), [7, 5, 4]);
+    assert_eq!(
+        array!(x down_from 9 if x % 2 == 0, built => x + built.len(); 2),
+        [8, 7]
+    );
+
+    let pairs = [(1, 3), (4, 2), (2, 7), (5, 6), (0, 0)];
+    assert_eq!(
+        collect_array!((a, b) if a < b in pairs => b - a; 3),
+        Some([2, 5, 1])
+    );
+    assert_eq!(
+        collect_array!((a, b) if a < b in pairs => b - a; where a > 1; 2),
+        Some([5, 1])
+    );
+    assert_eq!(
+        collect_array!((a, _) if a > 0 in pairs, c if c % 2 == 0 in 0.. => a + c; 2),
+        Some([1, 4])
+    );
+    assert_eq!(
+        collect_array!((a, b) if a < b in pairs; i => b - a + i; else |_| 0; 4),
+        [2, 6, 3, 0]
+    );
+}
+
+#[test]
+fn test_collect_slice_array() {
+    let buffer = [1u8, 2, 3, 4, 5, 6];
+    assert_eq!(collect_slice_array!(&buffer[1..4]; 3), [2, 3, 4]);
+    assert_eq!(collect_slice_array!(&buffer; 6), buffer);
+    assert_eq!(collect_slice_array!(&buffer[..0]; 0), []);
+}
+
+#[test]
+#[should_panic(expected = "array of 4 elements from slice of 6 elements")]
+fn test_collect_slice_array_panic() {
+    let buffer = [1u8, 2, 3, 4, 5, 6];
+    collect_slice_array!(&buffer; 4);
+}
+
+#[test]
+fn test_inferred_length() {
+    fn sum(values: [u32; 4]) -> u32 {
+        values.iter().sum()
+    }
+
+    fn indices<const N: usize>() -> [usize; N] {
+        array![x => x; _]
+    }
+
+    assert_eq!(sum(array![x => x * 2; _]), 12);
+    assert_eq!(sum(array![1; _]), 4);
+    assert_eq!(indices::<3>(), [0, 1, 2]);
+
+    let values: [std::string::String; 2] = array![clone "a".into(); _];
+    assert_eq!(values, ["a", "a"]);
+
+    let values: Option<[u8; 2]> = try_array![x => x; where x > 250; _];
+    assert_eq!(values, Some([251, 252]));
+    let values: Option<[u8; 2]> = try_array![0; _];
+    assert_eq!(values, Some([0, 0]));
+}
+
+#[test]
+fn test_const_array() {
+    const SQUARES: [usize; 4] = const_array![x => x * x; 4];
+    const EVEN: [u8; 3] = const_array![x => x as u8; where x % 2 == 0, x > 0; 3];
+    const ZERO: [u32; 0] = const_array![1; 0];
+    static LUT: [u8; 5] = const_array![x @ 0..=9 => b'0' + x as u8; 5];
+    const STRINGS: [&str; 2] = const_array!["a"; 2];
+
+    assert_eq!(SQUARES, [0, 1, 4, 9]);
+    assert_eq!(EVEN, [2, 4, 6]);
+    assert_eq!(ZERO, []);
+    assert_eq!(&LUT, b"01234");
+    assert_eq!(STRINGS, ["a", "a"]);
+    assert_eq!(const_array![x => x + 1; 2], [1, 2]);
+}
+
+#[test]
+fn test_array_map() {
+    assert_eq!(array_map!([1, 2, 3], |x| x * 2), [2, 4, 6]);
+    assert_eq!(
+        array_map!([(1, 'a'), (2, 'b')], (n, c) => (c, n)),
+        [('a', 1), ('b', 2)]
+    );
+    assert_eq!(array_map!([0u8; 0], |x| x as u32), []);
+
+    let strings = array_map!([1, 2], |x| std::format!("{}", x));
+    assert_eq!(array_map!(strings, s => s.len()), [1, 1]);
+}
+
+#[test]
+fn test_map_array() {
+    use std::string::ToString;
+
+    let names = map_array![[1, 2], x => x.to_string()];
+    assert_eq!(names, ["1", "2"]);
+    assert_eq!(map_array![names, |s| s.len()], [1, 1]);
+}
+
+#[test]
+fn test_array_map_panic() {
+    use std::{panic, rc::Rc};
+
+    let value = Rc::new(());
+    let source = [value.clone(), value.clone(), value.clone(), value.clone()];
+    let result = panic::catch_unwind(panic::AssertUnwindSafe(|| {
+        let mut count = 0;
+        array_map!(source, |x| {
+            count += 1;
+            assert!(count < 3, "boom");
+            (x, count)
+        })
+    }));
+    assert!(result.is_err());
+    assert_eq!(Rc::strong_count(&value), 1);
+}
+
+#[test]
+fn test_uninit_array() {
+    use core::mem::MaybeUninit;
+
+    let mut array = uninit_array![std::string::String; 3];
+    for (i, slot) in array.iter_mut().enumerate() {
+        slot.write(std::format!("{}", i));
+    }
+    let array = unsafe { assume_init_array(array) };
+    assert_eq!(array, ["0", "1", "2"]);
+
+    let array: [MaybeUninit<u8>; 0] = uninit_array();
+    assert_eq!(unsafe { assume_init_array(array) }, []);
+
+    const ARRAY: [u8; 2] = {
+        let mut array = uninit_array::<u8, 2>();
+        array[0] = MaybeUninit::new(1);
+        array[1] = MaybeUninit::new(2);
+        unsafe { assume_init_array(array) }
+    };
+    assert_eq!(ARRAY, [1, 2]);
+}
+
+#[test]
+fn test_array_zip() {
+    use std::string::String;
+
+    let names = [String::from("a"), String::from("b")];
+    let mut counts = [1, 2];
+
+    assert_eq!(array_zip!(&names, counts), [(&names[0], 1), (&names[1], 2)]);
+
+    for (count, name) in array_zip!(&mut counts, &names) {
+        *count += name.len();
+    }
+    assert_eq!(counts, [2, 3]);
+
+    assert_eq!(
+        array_zip!(names, counts, [true, false]),
+        [(String::from("a"), 2, true), (String::from("b"), 3, false)]
+    );
+    assert_eq!(array_zip!([1, 2]), [(1,), (2,)]);
+    assert_eq!(array_zip!([0u8; 0], [0u32; 0]), []);
+}
+
+#[cfg(feature = "alloc")]
+#[test]
+fn test_boxed_array() {
+    use std::{boxed::Box, string::ToString};
+
+    let values = boxed_array![x => x.to_string(); 3];
+    assert_eq!(*values, ["0", "1", "2"]);
+
+    let values: Box<[u8; 2]> = boxed_array![x => x; where x > 100; rev; _];
+    assert_eq!(*values, [255, 254]);
+
+    assert_eq!(*boxed_array![(); 0], []);
+}
+
+#[cfg(feature = "alloc")]
+#[test]
+fn test_boxed_array_clauses() {
+    use std::boxed::Box;
+
+    assert_eq!(
+        *boxed_array![state acc = 0; x => { acc += x; acc }; where x % 2 == 1; 3],
+        [1, 4, 9]
+    );
+    assert_eq!(*boxed_array![x => x; fill rev; 3], [0, 1, 2]);
+    assert_eq!(
+        *boxed_array![x, built => built.len() * 10 + x; fill rev; 3],
+        [20, 11, 2]
+    );
+    assert_eq!(*boxed_array![x => yield [x, x]; distinct; 2], [0, 1]);
+
+    fn bail() -> Option<Box<[u8; 2]>> {
+        Some(boxed_array![x => if x < 1 { x } else { return None }; 2])
+    }
+    assert_eq!(bail(), None);
+}
+
+#[cfg(feature = "alloc")]
+#[test]
+fn test_boxed_array_large() {
+    // 16MiB would overflow test thread stack if built on the stack.
+    let values = boxed_array![x: u64 => x; 1 << 21];
+    assert!(values.iter().enumerate().all(|(i, &x)| i as u64 == x));
+}
+
+#[cfg(feature = "alloc")]
+#[test]
+fn test_boxed_array_panic() {
+    use std::{cell::Cell, panic, rc::Rc};
+
+    let drops = Rc::new(Cell::new(0));
+
+    let result = panic::catch_unwind(panic::AssertUnwindSafe(
+        || boxed_array![x => if x < 3 { Counted::new(&drops) } else { panic!() }; 5],
+    ));
+    assert!(result.is_err());
+    assert_eq!(drops.get(), 3);
+}
+
+#[test]
+fn test_array_unzip() {
+    use std::string::String;
+
+    let pairs = [(1, String::from("a")), (2, String::from("b"))];
+    let (numbers, names) = array_unzip!(pairs);
+    assert_eq!(numbers, [1, 2]);
+    assert_eq!(names, ["a", "b"]);
+
+    let (numbers, names) = array_unzip!(array_zip!(numbers, names));
+    assert_eq!(numbers, [1, 2]);
+    assert_eq!(names, ["a", "b"]);
+
+    let (left, right): ([u8; 0], [u8; 0]) = array_unzip!([]);
+    assert_eq!((left, right), ([], []));
+}
+
+#[test]
+#[should_panic(
+    expected = "whole 'u8' space, 2 of 5 elements initialized; predicate: `x % 200 == 0`"
+)]
+fn test_exhausted_message() {
+    array!(x: u8 => x; where x % 200 == 0; 5);
+}
+
+#[test]
+#[should_panic(
+    expected = "attempt budget, 1 of 2 elements initialized; predicates: `x > 3`, `x % 5 == 0`"
+)]
+fn test_exhausted_message_predicates() {
+    array!(x => x; where x > 3, x % 5 == 0; tries 8; 2);
+}
+
+#[test]
+fn test_array_concat() {
+    use std::string::{String, ToString};
+
+    let left = [String::from("a"), String::from("b")];
+    let right = [String::from("c")];
+
+    assert_eq!(array_concat!(&left, &right; 3), ["a", "b", "c"]);
+    assert_eq!(array_concat!(left, right; 3), ["a", "b", "c"]);
+
+    let values: [u8; 2] = array_concat!([], [1, 2], []; _);
+    assert_eq!(values, [1, 2]);
+
+    let values = array_concat!(array![x => x.to_string(); 2], [String::new()]; 3);
+    assert_eq!(values, ["0", "1", ""]);
+}
+
+#[test]
+#[should_panic(expected = "array of 4 elements, only 3 elements provided")]
+fn test_array_concat_short() {
+    let _ = array_concat!([1, 2], [3]; 4);
+}
+
+#[test]
+fn test_batch() {
+    use std::{cell::Cell, rc::Rc, vec};
+
+    assert_eq!(array![x => yield [x, x + 100]; 5], [0, 100, 1, 101, 2]);
+    assert_eq!(array![x => yield [x; 2]; distinct; 3], [0, 1, 2]);
+    assert_eq!(array![x => yield vec![x; x]; 4], [1, 2, 2, 3]);
+    assert_eq!(
+        try_array![x: u8 => yield [x, x]; where x > 254; 4],
+        None::<[u8; 4]>
+    );
+
+    let drops = Rc::new(Cell::new(0));
+
+    let values = array![_ => yield [Counted::new(&drops), Counted::new(&drops)]; 3];
+    assert_eq!(
+        drops.get(),
+        1,
+        "Excess element of the last batch is dropped"
+    );
+
+    drop(values);
+    assert_eq!(drops.get(), 4);
+}
+
+#[test]
+fn test_array_result() {
+    use std::{cell::Cell, rc::Rc};
+
+    let drops = Rc::new(Cell::new(0));
+
+    let make = |x: u32, fail: u32| {
+        if x == fail {
+            Err(x)
+        } else {
+            Ok(Counted::new(&drops))
+        }
     };
 
-    ($e:expr; $ph:pat in $ih:expr $( , $pt:pat in $it:expr )* $(; where $($cond:expr),+ )? ; $n:expr) => {{
-        #[allow(unused_mut)]
-        let mut array = $crate::PartiallyInitArray::<_, $n>::uninit();
+    for (fail, dropped) in [(0, 0), (2, 2), (4, 4)] {
+        drops.set(0);
+        let values = array_result![x => make(x, fail); 5];
+        assert!(matches!(values, Err(x) if x == fail));
+        assert_eq!(drops.get(), dropped);
+    }
+
+    drops.set(0);
+    let values = array_result![x => make(x, 10); where x % 2 == 0; 3];
+    assert!(values.is_ok());
+    drop(values);
+    assert_eq!(drops.get(), 3);
+
+    fn propagate(drops: &Rc<Cell<u32>>) -> Result<[Counted; 3], u32> {
+        Ok(array![x: u32 => if x < 2 { Counted::new(drops) } else { Err(x)? }; 3])
+    }
+
+    drops.set(0);
+    assert!(matches!(propagate(&drops), Err(2)));
+    assert_eq!(drops.get(), 2);
+
+    for (fail, dropped) in [(0, 0), (2, 2), (4, 4)] {
+        drops.set(0);
+        let results = (0..5).map(|x| if x == fail { Err(x) } else { Ok(x) });
+        let values = collect_array_result![_ in results => Counted::new(&drops); 5];
+        assert!(matches!(values, Err(x) if x == fail));
+        assert_eq!(drops.get(), dropped);
+    }
+}
+
+#[test]
+fn test_array_split() {
+    use std::string::{String, ToString};
+
+    let (left, right) = array_split!(array![x => x.to_string(); 3]; 1, 2);
+    assert_eq!(left, ["0"]);
+    assert_eq!(right, ["1", "2"]);
+
+    let (left, right): ([String; 0], [String; 2]) = array_split!(right; _, _);
+    assert_eq!(left, [""; 0]);
+    assert_eq!(right, ["1", "2"]);
+
+    let (left, right) = array_split!(array_concat!(left, right; 2); 2, 0);
+    assert_eq!(left, ["1", "2"]);
+    assert_eq!(right, [""; 0]);
+}
+
+#[test]
+fn test_grid() {
+    assert_eq!(
+        array![(i, j) over 4 x 3 => i * 10 + j; 12],
+        [0, 1, 2, 10, 11, 12, 20, 21, 22, 30, 31, 32]
+    );
+    assert_eq!(
+        array![(i, j) over 4 x 3 => i * 10 + j; where i != 2; 9],
+        [0, 1, 2, 10, 11, 12, 30, 31, 32]
+    );
+    assert_eq!(
+        array![(i, j) over 2 x 2 => (i, j); rev; 4],
+        [(1, 1), (1, 0), (0, 1), (0, 0)]
+    );
+    assert_eq!(
+        array![(i, _) over 3 x 2, built => i; where !built.contains(&i); 3],
+        [0, 1, 2]
+    );
+    assert_eq!(
+        try_array![(i, j) over 4 x 3 => i + j; where i == j; 4],
+        None
+    );
+    assert_eq!(array![(i, j) over 0 x 3 => i + j; 0], []);
+    assert_eq!(array![(i, 1) over 2 x 3 => i; 2], [0, 1]);
+    assert_eq!(
+        array![(i, j) over 2 x 3 if i != j, built => i * 10 + j + built.len(); 4],
+        [1, 3, 12, 15]
+    );
+    assert_eq!(
+        array![(0, j) over 2 x 3 => j, (i, j) => i * 10 + j; 4],
+        [0, 1, 2, 10]
+    );
+}
+
+#[test]
+#[should_panic(
+    expected = "using all 4 by 3 pairs, 3 of 4 elements initialized; predicate: `i == j`"
+)]
+fn test_grid_exhausted() {
+    array![(i, j) over 4 x 3 => i + j; where i == j; 4];
+}
+
+#[test]
+#[should_panic(expected = "array of 4 elements using 2 by 3 pairs without predicates")]
+fn test_grid_length_mismatch() {
+    array![(i, j) over 2 x 3 => i + j; 4];
+}
+
+#[test]
+fn test_array_result_predicate() {
+    use std::{cell::Cell, rc::Rc};
+
+    let drops = Rc::new(Cell::new(0));
+
+    let check = |x: u32| if x < 5 { Ok(x & 1 == 0) } else { Err(x) };
+
+    let values = array_result![x => Ok(x); where try check(x); 3];
+    assert_eq!(values, Ok([0, 2, 4]));
+
+    let values = array_result![x => Ok::<_, u32>(Counted::new(&drops)); where try check(x); 4];
+    assert!(matches!(values, Err(5)));
+    assert_eq!(drops.get(), 3);
+
+    let values = array_result![x => Ok(x); where x > 0; where try check(x); 1];
+    assert_eq!(values, Ok([2]));
+}
+
+#[test]
+#[should_panic(expected = "2 of 4 elements initialized; predicate: `check(x)`")]
+fn test_array_result_predicate_exhausted() {
+    let check = |x: u8| Ok::<_, ()>(x < 2);
+    let _ = array_result![x => Ok::<_, ()>(x); where try check(x); tries 10; 4];
+}
+
+#[test]
+fn test_array_flatten() {
+    use std::string::{String, ToString};
+
+    let rows = array![i => array![j => (i * 10 + j).to_string(); 2]; 3];
+    assert_eq!(array_flatten!(rows; 6), ["0", "1", "10", "11", "20", "21"]);
+
+    let values: [String; 0] = array_flatten!([[], [], []]; _);
+    assert_eq!(values, [""; 0]);
+}
+
+#[test]
+fn test_array2d() {
+    use std::string::ToString;
+
+    const ROWS: usize = 2;
+    const COLS: usize = 3;
+
+    assert_eq!(
+        array2d![(r, c) => r * COLS + c; ROWS, COLS],
+        [[0, 1, 2], [3, 4, 5]]
+    );
+    assert_eq!(array2d![(r, c) => (r + c).to_string(); 1, 2], [["0", "1"]]);
+    assert_eq!(
+        array2d![(r, c) => (r, c); where r != c; else (9, 9); 2, 2],
+        [[(9, 9), (0, 1)], [(1, 0), (9, 9)]]
+    );
+    assert_eq!(
+        array2d![(r, c) => r; where c < 5; else 9; ROWS, COLS],
+        [[0; COLS], [1; COLS]]
+    );
+    assert_eq!(
+        array2d![(r, c) => 1; where r == c; else 0; 3, 3],
+        [[1, 0, 0], [0, 1, 0], [0, 0, 1]]
+    );
+    assert_eq!(array2d![(); 0, 2], [[(); 2]; 0]);
+}
+
+#[cfg(all(test, feature = "futures"))]
+struct Delay<T> {
+    polls: usize,
+    value: Option<T>,
+    order: std::rc::Rc<core::cell::RefCell<std::vec::Vec<usize>>>,
+    index: usize,
+}
+
+#[cfg(all(test, feature = "futures"))]
+impl<T: Unpin> core::future::Future for Delay<T> {
+    type Output = T;
+
+    fn poll(
+        mut self: core::pin::Pin<&mut Self>,
+        cx: &mut core::task::Context<'_>,
+    ) -> core::task::Poll<T> {
+        if self.polls == 0 {
+            let index = self.index;
+            self.order.borrow_mut().push(index);
+            core::task::Poll::Ready(self.value.take().unwrap())
+        } else {
+            self.polls -= 1;
+            cx.waker().wake_by_ref();
+            core::task::Poll::Pending
+        }
+    }
+}
+
+#[cfg(feature = "futures")]
+#[test]
+fn test_join_array() {
+    use std::{cell::RefCell, rc::Rc, string::ToString, vec::Vec};
+
+    let order = Rc::new(RefCell::new(Vec::new()));
+    let values = futures::executor::block_on(join_array![i => Delay {
+        polls: 4 - i,
+        value: Some(i.to_string()),
+        order: order.clone(),
+        index: i,
+    }; 4]);
+
+    assert_eq!(values, ["0", "1", "2", "3"]);
+    assert_eq!(*order.borrow(), [3, 2, 1, 0]);
+
+    assert_eq!(
+        futures::executor::block_on(join_array![x => async move { x * 2 }; 3]),
+        [0, 2, 4]
+    );
+    assert_eq!(futures::executor::block_on(join_array![async { 1 }; 0]), []);
+}
+
+#[cfg(feature = "futures")]
+#[test]
+fn test_join_array_panic() {
+    use std::{cell::Cell, panic, rc::Rc};
+
+    let drops = Rc::new(Cell::new(0));
+
+    let result = panic::catch_unwind(panic::AssertUnwindSafe(|| {
+        futures::executor::block_on(join_array![i => {
+            let drops = drops.clone();
+            async move {
+                if i == 2 {
+                    panic!();
+                }
+                Counted::new(&drops)
+            }
+        }; 4])
+    }));
+
+    assert!(result.is_err());
+    assert_eq!(drops.get(), 2, "Completed outputs are dropped");
+}
+
+#[test]
+fn test_array_rev() {
+    use std::string::ToString;
+
+    assert_eq!(
+        array_rev(array![x => x.to_string(); 4]),
+        ["3", "2", "1", "0"]
+    );
+    assert_eq!(array_rev([1]), [1]);
+    assert_eq!(array_rev::<u8, 0>([]), []);
+}
+
+#[cfg(feature = "rayon")]
+#[test]
+fn test_par_array() {
+    use std::{string::ToString, thread, time::Duration};
+
+    let values = par_array![x => {
+        thread::sleep(Duration::from_millis(8 - x as u64));
+        x.to_string()
+    }; 8];
+    assert_eq!(values, array![x => x.to_string(); 8]);
+
+    let values: [u8; 3] = par_array![7; _];
+    assert_eq!(values, [7; 3]);
+    assert_eq!(par_array![(); 0], []);
+}
 
-        let iter = $crate::IntoIterator::into_iter($ih);
-        $( let iter = iter.zip($it); )*
-        let mut iter = iter;
+#[cfg(feature = "rayon")]
+#[test]
+fn test_par_array_panic() {
+    use std::{
+        panic,
+        sync::{
+            atomic::{AtomicU32, Ordering},
+            Arc,
+        },
+    };
 
-        loop {
-            if array.is_init() {
-                break;
+    let created = AtomicU32::new(0);
+    let drops = Arc::new(AtomicU32::new(0));
+
+    let result = panic::catch_unwind(panic::AssertUnwindSafe(|| {
+        par_array![x => {
+            if x == 37 {
+                panic!();
             }
+            created.fetch_add(1, Ordering::Relaxed);
+            Counted::new(&drops)
+        }; 64]
+    }));
 
-            match iter.next() {
-                None => break,
-                Some($crate::pattern_list!($ph, $( $pt, )*)) => {
-                    #[allow(unreachable_code)]
-                    {
-                        $($(
-                            #[allow(unused_variables)]
-                            #[warn(unreachable_code)]
-                            let cond = $cond;
+    assert!(result.is_err());
+    assert_eq!(
+        created.load(Ordering::Relaxed),
+        drops.load(Ordering::Relaxed)
+    );
+}
 
-                            if <bool as $crate::Not>::not(cond) { continue; }
-                        )+)?
+#[test]
+fn test_array_enumerate() {
+    use std::string::{String, ToString};
 
-                        #[allow(unused_variables)]
-                        let elem;
+    let values = array_enumerate!(array![x => x.to_string(); 2]);
+    assert_eq!(values, [(0, String::from("0")), (1, String::from("1"))]);
 
-                        #[allow(unused_variables)]
-                        let dont_continue_in_element_expression_without_label;
+    let values = array_enumerate!([(); 2], start: usize::MAX - 1);
+    assert_eq!(values, [(usize::MAX - 1, ()), (usize::MAX, ())]);
+    assert_eq!(array_enumerate!([0u8; 0], start: 3), []);
+}
 
-                        loop {
-                            #[allow(unused)]
-                            {
-                                dont_continue_in_element_expression_without_label = ();
-                            }
+#[test]
+fn test_filter_map() {
+    let mut calls = 0;
+    let values = array![x: u32 => yield { calls += 1; x.checked_sub(2) }; 3];
+    assert_eq!(values, [0, 1, 2]);
+    assert_eq!(calls, 5);
 
-                            #[allow(unused_variables)]
-                            #[warn(unreachable_code)]
-                            let value = $e;
+    let mut calls = 0;
+    let values = collect_array![x in 0u32.. => yield { calls += 1; x.checked_sub(2) }; 3];
+    assert_eq!(values, Some([0, 1, 2]));
+    assert_eq!(calls, 5);
 
-                            elem = value;
+    let mut calls = 0;
+    let values = collect_array![x in 0u32..4, y in 1.. => yield { calls += 1; x.checked_sub(y) }; where x > 0; 3];
+    assert_eq!(values, None);
+    assert_eq!(calls, 3);
 
-                            break $crate::DontBreakFromElementExpressionWithoutLabel;
-                        };
+    assert_eq!(
+        collect_array![x in 0..5 => yield [x, x]; 3],
+        Some([0, 0, 1])
+    );
+}
 
-                        unsafe {
-                            array.write(elem);
-                        }
-                    }
-                }
-                #[allow(unreachable_patterns)]
-                _ => continue,
+#[test]
+fn test_zip_arrays() {
+    use std::string::{String, ToString};
+
+    let names = [String::from("a"), String::from("b")];
+    let counts = [1, 2];
+    let flags = [true, false];
+
+    assert_eq!(
+        zip_arrays![names, counts, flags => names.repeat(counts) + &flags.to_string()],
+        ["atrue", "bbfalse"]
+    );
+    assert_eq!(zip_arrays![counts => counts * 2], [2, 4]);
+    assert_eq!(
+        zip_arrays![(a, b) in [(1, 2), (3, 4)], c in counts => a + b + c],
+        [4, 9]
+    );
+}
+
+#[test]
+fn test_array_scan() {
+    use std::{cell::Cell, panic, rc::Rc, string::ToString};
+
+    let values = array_scan!(array![x => x.to_string(); 3], 1, |n, s| {
+        *n *= 2;
+        s.repeat(*n)
+    });
+    assert_eq!(values, ["00", "1111", "22222222"]);
+
+    assert_eq!(array_scan!([1, 2, 3], 0, |_, x| x), [1, 2, 3]);
+
+    let drops = Rc::new(Cell::new(0));
+
+    let result = panic::catch_unwind(panic::AssertUnwindSafe(|| {
+        array_scan!([0, 1, 2, 3], Counted::new(&drops), |acc, x| {
+            if x == 2 {
+                panic!();
             }
-        }
+            Counted::new(&acc.1)
+        })
+    }));
+    assert!(result.is_err());
+    assert_eq!(drops.get(), 3, "Accumulator and two elements are dropped");
+}
 
-        array.try_init()
-    }};
+#[test]
+fn test_scan_array() {
+    let mut calls = 0;
+    let values = scan_array![acc = 0; x => { calls += 1; acc += x; acc }; where x % 3 != 0; 4];
+    assert_eq!(values, [1, 3, 7, 12]);
+    assert_eq!(calls, 4, "Accumulator is updated once per written element");
 
-    ($( $p:pat in $i:expr ),+ => $e:expr $(; where $($cond:expr),+ )? ; $n:expr) => {
-        $crate::collect_array!($e; $($p in $i),+ $( ; where $($cond),+ )? ; $n)
-    };
+    let values: Option<[u8; 2]> =
+        try_array![state acc = 0; x => { acc += x; acc }; where x > 254; _];
+    assert_eq!(values, None);
 }
 
 #[test]
-fn test_expression_repeat() {
-    let mut i = 0;
-    assert_eq!(array!({ i+=1; i }; 2), [1, 2]);
+fn test_fill_rev() {
+    use std::{cell::Cell, panic, rc::Rc, vec::Vec};
+
+    let mut order = Vec::new();
+    let values = array![x => { order.push(x); x * 10 }; fill rev; 4];
+    assert_eq!(values, [0, 10, 20, 30]);
+    assert_eq!(order, [3, 2, 1, 0]);
+
+    assert_eq!(
+        array![x => x; where x % 2 == 0; fill rev; else -1; 3],
+        [-1, 0, 2]
+    );
+    assert_eq!(
+        array![x => x; where x < 2; fill rev; tries 4; else 9; 4],
+        [9, 9, 0, 1]
+    );
+    assert_eq!(try_array![x => x; fill rev; 2], Some([0, 1]));
+    assert_eq!(
+        try_array![x: usize => x; where x % 2 == 0; fill rev; 3],
+        None
+    );
+    assert_eq!(array![x => 2 - x; sorted; fill rev; 3], [0, 1, 2]);
+    assert_eq!(array![x => x; fill rev; sorted by |x| 2 - x; 3], [2, 1, 0]);
+    assert_eq!(
+        array![x, built => built.first().map_or(0, |b| b * 10) + x; fill rev; 3],
+        [210, 21, 2]
+    );
+
+    let drops = Rc::new(Cell::new(0));
+
+    let result = panic::catch_unwind(panic::AssertUnwindSafe(
+        || array![x => if x > 1 { Counted::new(&drops) } else { panic!() }; fill rev; 5],
+    ));
+    assert!(result.is_err());
+    assert_eq!(drops.get(), 3);
 }
 
+#[cfg(feature = "alloc")]
 #[test]
-fn test_comprehension_repeat() {
-    assert_eq!(array!(x => x * 2; 3), [0, 2, 4]);
-    assert_eq!(array!(x => x * 2; where x & 1 == 1; 3), [2, 6, 10]);
+fn test_fill_rev_drops() {
+    use std::{cell::Cell, panic, rc::Rc};
+
+    let drops = Rc::new(Cell::new(0));
+
+    let result = panic::catch_unwind(panic::AssertUnwindSafe(
+        || boxed_array![x => if x > 1 { Counted::new(&drops) } else { panic!() }; fill rev; 5],
+    ));
+    assert!(result.is_err());
+    assert_eq!(drops.get(), 3);
+
+    drops.set(0);
+    let result = panic::catch_unwind(panic::AssertUnwindSafe(
+        || vec_fu![x => if x > 1 { Counted::new(&drops) } else { panic!() }; fill rev; 5],
+    ));
+    assert!(result.is_err());
+    assert_eq!(drops.get(), 3);
+
+    drops.set(0);
+    let values = vec_fu![_ => Counted::new(&drops); fill rev; 4];
+    assert_eq!(drops.get(), 0);
+    drop(values);
+    assert_eq!(drops.get(), 4);
 }
 
 #[test]
-fn test_comprehension_iter() {
+#[should_panic(expected = "back-to-front using one value per element, 2 of 3 elements initialized")]
+fn test_fill_rev_exhausted() {
+    let _: [usize; 3] = array![x => x; where x % 2 == 0; fill rev; 3];
+}
+
+#[test]
+fn test_array_init_with() {
+    use std::{cell::Cell, panic, rc::Rc, string::ToString};
+
+    const N: usize = 3;
+
+    assert_eq!(array_init_with!(N, |i| i.to_string()), ["0", "1", "2"]);
+    assert_eq!(array_init_with!(N - 1, |_| ()), [(); 2]);
+
+    let mut calls = 0;
+    let values: [usize; 2] = array_init_with!(_, |i| {
+        calls += 1;
+        i + calls
+    });
+    assert_eq!(values, [1, 3]);
+
+    let drops = Rc::new(Cell::new(0));
+
+    let result = panic::catch_unwind(panic::AssertUnwindSafe(|| {
+        array_init_with!(4, |i| if i < 2 {
+            Counted::new(&drops)
+        } else {
+            panic!()
+        })
+    }));
+    assert!(result.is_err());
+    assert_eq!(drops.get(), 2);
+}
+
+#[cfg(feature = "alloc")]
+#[test]
+fn test_vec_fu() {
+    use std::{string::ToString, vec::Vec};
+
+    let n = 3;
+    assert_eq!(vec_fu![x => x.to_string(); n], ["0", "1", "2"]);
+    assert_eq!(vec_fu![x: u8 => x; where x > 100; rev; n - 1], [255, 254]);
+    assert_eq!(vec_fu![7; n], [7, 7, 7]);
+    assert_eq!(vec_fu![1, 2, 3,], [1, 2, 3]);
+    assert_eq!(vec_fu![(); 0], Vec::<()>::new());
     assert_eq!(
-        collect_array!(x * 2; x in 1..3; 3),
-        None,
-        "There's not enough elements in iterator"
+        vec_fu![x, built => built.len() * 10 + x; fill rev; n],
+        [20, 11, 2]
     );
+
+    let empty: Vec<u8> = vec_fu![];
+    assert!(empty.is_empty());
+}
+
+#[cfg(feature = "alloc")]
+#[test]
+#[should_panic(expected = "2 of 4 elements initialized; predicate: `x < 2`")]
+fn test_vec_fu_exhausted() {
+    let _ = vec_fu![x: u8 => x; where x < 2; 4];
+}
+
+#[test]
+fn test_sorted() {
+    assert_eq!(array![x => 3 - x; sorted; 4], [0, 1, 2, 3]);
     assert_eq!(
-        collect_array!(x * 2; x in 1..; 3),
-        Some([2, 4, 6]),
-        "1*2, 2*2, 3*2"
+        array![x: i32 => x * x; where x % 2 == 1; sorted by |&v| -v; 3],
+        [25, 9, 1]
     );
+    assert_eq!(array![x => x; rev; sorted; 3], [0, 1, 2]);
+    assert_eq!(try_array![x: u8 => x; where x > 253; sorted; 3], None);
+
+    // Keys are distinct, so unstable sort gives a single order.
     assert_eq!(
-        collect_array!(x * y; x in 1.., y in (1..3).cycle(); where x > 3, y == 1; 3),
-        Some([5, 7, 9]),
-        "x = 1,2,3,4,5,6,7,8,9
-         y = 1,2,1,2,1,2,1,2,1
-         r = x,x,x,x,5,x,7,x,9"
+        array![x => (x * 7 % 5, x); sorted by |&(k, _)| k; 5],
+        [(0, 0), (1, 3), (2, 1), (3, 4), (4, 2)]
     );
 
+    assert_eq!(collect_array![[3, 1, 2]; sorted; 3], Some([1, 2, 3]));
+    assert_eq!(collect_array![[3, 1]; sorted; 3], None);
     assert_eq!(
-        collect_array!(x in 0.. => x * 2; where x & 1 == 1; 3),
-        Some(array!(x => x * 2; where x & 1 == 1; 3)),
+        collect_array![x in 0..10, y in (0..10).rev() => x * y; where x > 4; sorted by |&v| v; 3],
+        Some([14, 18, 20])
+    );
+}
+
+#[test]
+fn test_collect_exactly() {
+    use std::string::String;
+
+    let values: [String; 2] = collect_exactly!(["a", "b"].iter().map(|s| String::from(*s)); _);
+    assert_eq!(values, ["a", "b"]);
+
+    let empty: [u8; 0] = collect_exactly!([]; 0);
+    assert_eq!(empty, []);
+}
+
+#[test]
+#[should_panic(expected = "Failed to collect exactly 4 elements, iterator yielded only 3 elements")]
+fn test_collect_exactly_short() {
+    let _ = collect_exactly!(0..3; 4);
+}
+
+#[test]
+#[should_panic(expected = "Failed to collect exactly 2 elements, iterator yielded more elements")]
+fn test_collect_exactly_long() {
+    let _ = collect_exactly!(0..3; 2);
+}
+
+#[test]
+fn test_retry() {
+    use std::vec::Vec;
+
+    let mut tried = Vec::new();
+    let values = array![x => {
+        tried.push(x);
+        if x % 3 == 0 { continue 'retry } else { x }
+    }; where x > 0; retry 'retry; 4];
+    assert_eq!(values, [1, 2, 4, 5]);
+    assert_eq!(tried, [1, 2, 3, 4, 5]);
+
+    // Nested array retries only its own enumeration.
+    let values =
+        array![r => array![c => if c == r { continue 'inner } else { c }; retry 'inner; 2]; 3];
+    assert_eq!(values, [[1, 2], [0, 2], [0, 1]]);
+
+    assert_eq!(
+        try_array![x: u8 => if x > 0 { continue 'skip } else { x }; retry 'skip; 2],
+        None
+    );
+    assert_eq!(
+        array![state n = 0; x => { n += x; if n % 2 == 1 { continue 'odd } else { n } }; retry 'odd; 3],
+        [0, 6, 10]
     );
+}
+
+#[test]
+fn test_array_windows() {
+    use std::{string::String, vec::Vec};
+
+    let words: Vec<String> = ["a", "b", "c", "d"]
+        .iter()
+        .map(|s| String::from(*s))
+        .collect();
+    let windows = array_windows![words; window = 2; out = 3].unwrap();
+    assert_eq!(windows, [["a", "b"], ["b", "c"], ["c", "d"]]);
 
+    let mut iter = 0..10;
     assert_eq!(
-        collect_array!(x in 0.., _y in 1.., _z in 2.., _w in 3..5 => x; where x & 1 == 1; 3),
-        None,
+        array_windows![&mut iter; window = 3; out = 2],
+        Some([[0, 1, 2], [1, 2, 3]])
     );
+    assert_eq!(iter.next(), Some(4));
+
+    assert_eq!(array_windows![0..2; window = 3; out = 1], None);
+    assert_eq!(array_windows![0..1; window = 1; out = 0], Some([[0; 1]; 0]));
+    assert_eq!(array_windows![0..1; window = 1; out = 1], Some([[0]]));
 }
 
 #[test]
-fn test_bail() {
-    array!(return; 2);
-    panic!();
+fn test_array_from_iter() {
+    let mut iter = 0..10;
+    assert_eq!(array_from_iter!(&mut iter; 3), [0, 1, 2]);
+    assert_eq!(array_from_iter!(&mut iter; 0), [0; 0]);
+    assert_eq!(iter.next(), Some(3));
 }
 
 #[test]
-fn test_bail_condition() {
-    array!(_ => 0; where return; 1);
-    panic!();
+#[should_panic(
+    expected = "Failed to take 4 elements of type 'u16' from iterator, only 2 available"
+)]
+fn test_array_from_iter_short() {
+    let _ = array_from_iter!(0u16..2; 4);
 }
 
 #[test]
-fn test_bail_iter() {
-    collect_array!(_ in 1.. => 0; where return; 1);
-    panic!();
+fn test_list_fill() {
+    use std::{string::String, vec::Vec};
+
+    let mut calls = 0;
+    let values = array![1, 2, .., { calls += 1; 9 }; 5];
+    assert_eq!(values, [1, 2, 9, 9, 9]);
+    assert_eq!(calls, 3);
+
+    let values: [String; 2] = array![String::from("a"), String::from("b"), .., unreachable!(); _];
+    assert_eq!(values, ["a", "b"]);
+
+    let values = array![Vec::from([1]), .., Vec::new(); 3];
+    assert_eq!(values, [Vec::from([1]), Vec::new(), Vec::new()]);
+
+    assert_eq!(try_array![1, .., 0; 2], Some([1, 0]));
 }
 
+#[cfg(feature = "alloc")]
 #[test]
-#[should_panic]
-fn test_bail_panic() {
-    array!(return; 0);
-    panic!();
+fn test_list_fill_alloc() {
+    let n = 4;
+    assert_eq!(vec_fu![1, 2, .., 0; n], [1, 2, 0, 0]);
+    assert_eq!(*boxed_array![1, .., 0; 3], [1, 0, 0]);
 }
 
+#[cfg(feature = "alloc")]
 #[test]
-#[should_panic]
-fn test_bail_condition_panic() {
-    array!(_ => 0; where return; 0);
-    panic!();
+#[should_panic(expected = "Too many listed elements for vector length, 3 listed for 2 elements")]
+fn test_list_fill_vec_too_many() {
+    let n = 2;
+    let _ = vec_fu![1, 2, 3, .., 0; n];
+}
+
+#[test]
+fn test_array_chunks() {
+    use std::{cell::Cell, rc::Rc, vec::Vec};
+
+    let mut iter = 0..10;
+    assert_eq!(
+        array_chunks![&mut iter; chunk = 3; out = 2],
+        Some([[0, 1, 2], [3, 4, 5]])
+    );
+    assert_eq!(iter.next(), Some(6));
+
+    assert_eq!(array_chunks![0..1; chunk = 0; out = 2], Some([[0; 0]; 2]));
+    assert_eq!(array_chunks![0..1; chunk = 2; out = 0], Some([[0; 2]; 0]));
+
+    let drops = Rc::new(Cell::new(0));
+
+    let items: Vec<Counted> = (0..5).map(|_| Counted::new(&drops)).collect();
+    assert!(array_chunks![items; chunk = 2; out = 3].is_none());
+    assert_eq!(drops.get(), 5);
+}
+
+#[test]
+fn test_array_ref_windows() {
+    use std::string::String;
+
+    let names = [String::from("a"), String::from("b"), String::from("c")];
+    let windows: [[&String; 3]; 1] = array_windows!(&names, 3);
+    assert_eq!(windows, [[&names[0], &names[1], &names[2]]]);
+
+    let windows: [[&u8; 1]; 2] = array_windows!(&[1, 2], 1);
+    assert_eq!(windows, [[&1], [&2]]);
+
+    let slice: &[u8] = &[1, 2, 3];
+    assert_eq!(
+        array_windows![slice; window = 2; out = 2],
+        Some([[&1, &2], [&2, &3]])
+    );
+}
+
+#[test]
+fn test_flat_tuple_pattern() {
+    let values =
+        collect_array![(a, 2, c) in (0.., [2, 3, 2, 2], ['a', 'b', 'c', 'd']) => (a, c); 3];
+    assert_eq!(values, Some([(0, 'a'), (2, 'c'), (3, 'd')]));
+
+    let values = collect_array![
+        (a, _, Some(c), d,) in (1.., [(); 5], [Some(1), None, Some(3), Some(4), None], "vwxyz".chars(),) => a * c + d as u32;
+        where d != 'y';
+        2
+    ];
+    assert_eq!(values, Some([1 + 'v' as u32, 9 + 'x' as u32]));
+
+    // Tuple pattern over a single iterator keeps working.
+    let values = collect_array![(x, y) in ([(1, 2), (3, 4)]) => x + y; 2];
+    assert_eq!(values, Some([3, 7]));
+
+    // Same bindings with separate patterns.
+    let values =
+        collect_array![a in 0.., 2 in [2, 3, 2, 2], c in ['a', 'b', 'c', 'd'] => (a, c); 3];
+    assert_eq!(values, Some([(0, 'a'), (2, 'c'), (3, 'd')]));
+}
+
+#[test]
+fn test_spread() {
+    use std::{cell::Cell, panic, rc::Rc, string::String};
+
+    let a = [String::from("a"), String::from("b")];
+    let b = [String::from("c")];
+    let values = array![..a, ..b, String::from("-"); 4];
+    assert_eq!(values, ["a", "b", "c", "-"]);
+
+    let values = array![1, ..[2, 3], 4, .., 0; 6];
+    assert_eq!(values, [1, 2, 3, 4, 0, 0]);
+
+    // Spreads fill array entirely, fill expression is never evaluated.
+    let values: [u8; 3] = array![..[1], ..[2, 3], unreachable!(); _];
+    assert_eq!(values, [1, 2, 3]);
+
+    let drops = Rc::new(Cell::new(0));
+
+    let mut filled = 0;
+    let result = panic::catch_unwind(panic::AssertUnwindSafe(|| {
+        let small = [Counted::new(&drops), Counted::new(&drops)];
+        array![..small, {
+            filled += 1;
+            if filled > 2 { panic!() }
+            Counted::new(&drops)
+        }; 6]
+    }));
+    assert!(result.is_err());
+    assert_eq!(drops.get(), 4);
+}
+
+#[test]
+#[should_panic(expected = "Failed to spread 2 elements, only 1 elements left")]
+fn test_spread_too_long() {
+    let _ = array![1, 2, ..[3, 4], 0; 3];
+}
+
+#[test]
+fn test_array_ref_chunks() {
+    let values = [1, 2, 3, 4, 5, 6];
+
+    let (chunks, rest): ([&[i32; 3]; 2], _) = array_chunks!(&values, 3);
+    assert_eq!(chunks, [&[1, 2, 3], &[4, 5, 6]]);
+    assert!(rest.is_empty());
+
+    let (chunks, rest): ([&[i32; 4]; 1], _) = array_chunks!(&values, 4);
+    assert_eq!(chunks, [&[1, 2, 3, 4]]);
+    assert_eq!(rest, [5, 6]);
+
+    let (chunks, rest): ([&[i32; 8]; 0], _) = array_chunks!(&values, 8);
+    assert!(chunks.is_empty());
+    assert_eq!(rest, values);
+}
+
+#[test]
+fn test_flat_tuple_rest_pattern() {
+    let values = collect_array![(a, ..) in (0.., ['x'; 3], [true; 3]) => a; 3];
+    assert_eq!(values, Some([0, 1, 2]));
+
+    let values =
+        collect_array![(.., Some(c)) in (0.., [0; 4], [None, Some(1), None, Some(3)]) => c; 2];
+    assert_eq!(values, Some([1, 3]));
+
+    let values = collect_array![(a, .., 'y', d) in (0.., 10.., "xyxy".chars(), 20..) => a + d; 2];
+    assert_eq!(values, Some([22, 26]));
+
+    // Rest pattern may match no iterators.
+    let values = collect_array![(a, .., b) in (0.., 5..) => a * b; 2];
+    assert_eq!(values, Some([0, 6]));
+}
+
+#[test]
+fn test_collect_array_endless() {
+    use core::iter::{repeat, repeat_with};
+
+    let mut calls = 0;
+    let values = collect_array_endless![repeat_with(|| { calls += 1; calls }); 3];
+    assert_eq!(values, [1, 2, 3]);
+
+    let values = collect_array_endless![c in "ab".chars().cycle() => c; 5];
+    assert_eq!(values, ['a', 'b', 'a', 'b', 'a']);
+
+    let values = collect_array_endless![(i, x) in [1, 2].iter().copied().chain(repeat(0)).enumerate() => i * x; 4];
+    assert_eq!(values, [0, 2, 0, 0]);
+
+    let values = collect_array_endless![x in (0..).step_by(3), y in (0..).skip(1).map(|y| y * 2) => x + y; where x > 0; 2];
+    assert_eq!(values, [7, 12]);
+}
+
+#[test]
+#[should_panic(expected = "Endless iterator ran out of values")]
+fn test_collect_array_endless_empty_cycle() {
+    let _ = collect_array_endless![core::iter::empty::<u8>().cycle(); 1];
+}
+
+#[test]
+fn test_array_collect_ext() {
+    use crate::collect::ArrayCollectExt;
+    use std::string::String;
+
+    let mut iter = 0..5;
+    assert_eq!(iter.by_ref().collect_array::<2>(), Some([0, 1]));
+    assert_eq!(iter.next(), Some(2));
+    assert_eq!(iter.collect_array::<0>(), Some([]));
+
+    let values = ["a"]
+        .iter()
+        .map(|s| String::from(*s))
+        .collect_array_or::<3>(String::from("-"));
+    assert_eq!(values, ["a", "-", "-"]);
+    assert_eq!((0..5).collect_array_or::<3>(9), [0, 1, 2]);
+    assert_eq!((0..0).collect_array_or::<0>(9), []);
+}
+
+#[test]
+fn test_array_collect_ext_result() {
+    use crate::collect::ArrayCollectExt;
+    use std::{cell::Cell, rc::Rc};
+
+    let drops = Rc::new(Cell::new(0));
+    let mut iter = (0..5).map(|x| {
+        if x == 2 {
+            Err(x)
+        } else {
+            Ok(Counted::new(&drops))
+        }
+    });
+
+    assert!(matches!(iter.by_ref().collect_array_result::<4>(), Err(2)));
+    assert_eq!(drops.get(), 2);
+    assert!(matches!(iter.next(), Some(Ok(_))));
+
+    let mut iter = (0..5).map(Ok::<_, ()>);
+    assert_eq!(
+        iter.by_ref().collect_array_result::<3>(),
+        Ok(Some([0, 1, 2]))
+    );
+    assert_eq!(iter.next(), Some(Ok(3)));
+    assert_eq!(
+        (0..2).map(Ok::<_, ()>).collect_array_result::<3>(),
+        Ok(None)
+    );
+}
+
+#[test]
+fn test_collect_array_retry() {
+    use std::vec::Vec;
+
+    let mut tried = Vec::new();
+    let values = collect_array![x in 1.. => {
+        tried.push(x);
+        if x % 3 == 0 { continue 'retry } else { x }
+    }; where x != 4; retry 'retry; 3];
+    assert_eq!(values, Some([1, 2, 5]));
+    assert_eq!(tried, [1, 2, 3, 5]);
+
+    let mut source = 1..;
+    let values =
+        collect_array![x in source.by_ref() => if x > 2 { break 'done } else { x }; retry 'done; 4];
+    assert_eq!(values, None);
+    assert_eq!(source.next(), Some(4));
+
+    let values = collect_array![x in 1.. => if x > 2 { break 'done } else { x }; retry 'done; else |i| i * 10; 4];
+    assert_eq!(values, [1, 2, 20, 30]);
+
+    // Nested collection retries only its own iteration.
+    let values = collect_array![r in 0..3 => collect_array![
+        c in 0..3 => if c == r { continue 'inner } else { c }; retry 'inner; 2
+    ].unwrap(); 3];
+    assert_eq!(values, Some([[1, 2], [0, 2], [0, 1]]));
 }
@@ -0,0 +1,81 @@
+//! Parallel array construction with rayon.
+
+use core::{
+    mem::MaybeUninit,
+    ptr,
+    sync::atomic::{AtomicBool, Ordering},
+};
+
+use rayon::iter::{IntoParallelIterator, ParallelIterator};
+
+/// Pointer to array slots shared between rayon tasks.
+struct Slots<T>(*mut MaybeUninit<T>);
+
+// SAFETY: Each slot is written by one task only.
+unsafe impl<T: Send> Send for Slots<T> {}
+unsafe impl<T: Send> Sync for Slots<T> {}
+
+impl<T> Slots<T> {
+    /// Writes value into slot at `index`.
+    ///
+    /// # Safety
+    ///
+    /// Index must be in bounds and slot must not be written concurrently.
+    #[inline]
+    unsafe fn write(&self, index: usize, value: T) {
+        (*self.0.add(index)).write(value);
+    }
+}
+
+/// Drops written elements unless disarmed.
+struct Guard<'a, T, const N: usize> {
+    array: &'a mut [MaybeUninit<T>; N],
+    written: &'a [AtomicBool; N],
+}
+
+impl<T, const N: usize> Drop for Guard<'_, T, N> {
+    fn drop(&mut self) {
+        for (slot, written) in self.array.iter_mut().zip(self.written) {
+            if written.load(Ordering::Relaxed) {
+                unsafe {
+                    // SAFETY: Slot was written.
+                    ptr::drop_in_place(slot.as_mut_ptr());
+                }
+            }
+        }
+    }
+}
+
+/// Constructs array by calling `f` with each index on rayon thread pool.
+///
+/// If `f` panics, elements constructed by other calls are dropped.
+pub fn par_array<T, F, const N: usize>(f: F) -> [T; N]
+where
+    T: Send,
+    F: Fn(usize) -> T + Sync,
+{
+    let mut array = crate::uninit_array::<T, N>();
+    let written = crate::array![AtomicBool::new(false); N];
+
+    let guard = Guard {
+        array: &mut array,
+        written: &written,
+    };
+
+    let slots = Slots(guard.array.as_mut_ptr());
+    (0..N).into_par_iter().for_each(|index| {
+        let value = f(index);
+        unsafe {
+            // SAFETY: Each index is produced once.
+            slots.write(index, value);
+        }
+        written[index].store(true, Ordering::Relaxed);
+    });
+
+    core::mem::forget(guard);
+
+    unsafe {
+        // SAFETY: All slots were written.
+        crate::assume_init_array(array)
+    }
+}